@@ -2,11 +2,10 @@ use crate::game::Commodity;
 use crate::game::Manufactured;
 use crate::game::Resource;
 
-use std::collections::hash_map::Iter;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Result};
 use std::iter::FromIterator;
-use std::ops::AddAssign;
+use std::ops::{AddAssign, SubAssign};
 
 #[derive(Clone, Hash, Eq, PartialEq, Debug)]
 pub enum ComponentName {
@@ -17,6 +16,8 @@ pub enum ComponentName {
     BatteryComponent,
     RefineryOutputComponent,
     FactoryOutputComponent,
+    SightComponent,
+    ProductionComponent,
 }
 
 impl Display for ComponentName {
@@ -37,6 +38,31 @@ pub struct BatteryComponent {
     pub stored: u64,
 }
 
+// How far a structure reveals the map around it, and whether it surveys deposits.
+#[derive(Eq, PartialEq, Hash)]
+pub struct SightComponent {
+    pub radius: u16,
+    pub survey: bool,
+    pub active: bool,
+}
+
+// Input buffer and progress of a multi-tick production cycle.
+pub struct ProductionComponent {
+    pub cycle_ticks: u64,
+    pub progress: u64,
+    pub in_cycle: bool,
+    pub input: HashMap<Resource, u64>,
+    // Inputs taken out of the buffer for the running cycle.
+    pub consumed: HashMap<Resource, u64>,
+    // Refined goods some producers need on top of raw resources, kept apart like in stock.
+    pub manufactured_input: HashMap<Manufactured, u64>,
+    pub manufactured_consumed: HashMap<Manufactured, u64>,
+    pub output: u64,
+    // Finished goods held at the producer until they are shipped.
+    pub finished_manufactured: HashMap<Manufactured, u64>,
+    pub finished_commodities: HashMap<Commodity, u64>,
+}
+
 pub struct MineOutputComponent {
     pub resource_out: u64,
     pub manufactured_out: u64,
@@ -100,24 +126,94 @@ impl ResourceStorageComponent {
         self.resources.get_mut(group).unwrap().add_assign(amount);
     }
 
+    // Takes up to the amount out, returns how much was there to take.
+    pub fn resource_take(&mut self, group: &Resource, amount: u64) -> u64 {
+        let stored = match self.resources.get_mut(group) {
+            Some(stored) => stored,
+            None => return 0,
+        };
+
+        let taken = amount.min(*stored);
+        stored.sub_assign(taken);
+        return taken;
+    }
+
     pub fn resources(&self) -> Vec<&Resource> {
         return Vec::from_iter(self.resources.keys());
     }
 }
 
-impl FactoryOutputComponent {
-    pub fn resources(&self) -> Iter<'_, Resource, u64> {
-        self.resource_required.iter()
+impl RefineryOutputComponent {
+    pub fn resource_required_sum(&self) -> u64 {
+        self.energy_required.values().sum()
+    }
+
+    // Everything one cycle consumes, over all products of the refinery.
+    pub fn resource_required_total(&self) -> HashMap<Resource, u64> {
+        let mut total = HashMap::new();
+
+        for (_, required) in self.resource_required.iter() {
+            for (resource, amount) in required {
+                total.entry(*resource).or_insert(0).add_assign(*amount);
+            }
+        }
+
+        return total;
     }
 }
 
-impl RefineryOutputComponent {
-    pub fn resources(&self) -> Iter<'_, Manufactured, HashMap<Resource, u64>> {
-        self.resource_required.iter()
+impl ProductionComponent {
+    pub fn new(cycle_ticks: u64) -> ProductionComponent {
+        return ProductionComponent {
+            cycle_ticks,
+            progress: 0,
+            in_cycle: false,
+            input: HashMap::new(),
+            consumed: HashMap::new(),
+            manufactured_input: HashMap::new(),
+            manufactured_consumed: HashMap::new(),
+            output: 0,
+            finished_manufactured: HashMap::new(),
+            finished_commodities: HashMap::new(),
+        };
     }
 
-    pub fn resource_required_sum(&self) -> u64 {
-        self.energy_required.values().sum()
+    // Every finished good waiting to be shipped, whatever its kind.
+    pub fn finished(&self) -> u64 {
+        let manufactured: u64 = self.finished_manufactured.values().sum();
+        let commodities: u64 = self.finished_commodities.values().sum();
+
+        return manufactured + commodities;
+    }
+
+    pub fn buffered(&self, resource: &Resource) -> u64 {
+        return self.input.get(resource).copied().unwrap_or(0);
+    }
+
+    // The first input the buffer cannot cover for a whole cycle.
+    pub fn missing(&self, required: &HashMap<Resource, u64>) -> Option<Resource> {
+        return required
+            .iter()
+            .find(|(resource, amount)| self.buffered(resource) < **amount)
+            .map(|(resource, _)| *resource);
+    }
+
+    pub fn buffered_manufactured(&self, manufactured: &Manufactured) -> u64 {
+        return self
+            .manufactured_input
+            .get(manufactured)
+            .copied()
+            .unwrap_or(0);
+    }
+
+    pub fn missing_manufactured(
+        &self,
+        required: &HashMap<Manufactured, u64>,
+    ) -> Option<Manufactured> {
+        return required
+            .iter()
+            .find(|(manufactured, amount)| self.buffered_manufactured(manufactured) < **amount)
+            .map(|(manufactured, _)| *manufactured);
     }
 }
 
@@ -184,6 +280,12 @@ pub enum ComponentGroup {
     Battery {
         component: BatteryComponent,
     },
+    Sight {
+        component: SightComponent,
+    },
+    Production {
+        component: ProductionComponent,
+    },
 }
 
 impl Display for ComponentGroup {
@@ -196,6 +298,8 @@ impl Display for ComponentGroup {
             ComponentGroup::ResourceStorage { .. } => ComponentName::ResourceStorageComponent,
             ComponentGroup::CommodityStorage { .. } => ComponentName::CommodityStorageComponent,
             ComponentGroup::Battery { .. } => ComponentName::BatteryComponent,
+            ComponentGroup::Sight { .. } => ComponentName::SightComponent,
+            ComponentGroup::Production { .. } => ComponentName::ProductionComponent,
         };
 
         write!(f, "{}", name)
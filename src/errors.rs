@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result};
+
+use crate::component::ComponentName;
+use crate::game::{Flora, Footprint, Position, Resource};
+use crate::rovers::RoverKind;
+use crate::structures::StructureGroup;
+use crate::util::format_resources;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ComponentError {
+    Missing(ComponentName),
+}
+
+impl Display for ComponentError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            ComponentError::Missing(name) => write!(f, "{} is missing in this structure", name),
+        }
+    }
+}
+
+impl Error for ComponentError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlacementError {
+    // A structure already stands on the tile.
+    Occupied {
+        position: Position,
+        structure: String,
+    },
+    // Part of the footprint falls outside the map.
+    OutOfBounds {
+        group: StructureGroup,
+        footprint: Footprint,
+    },
+    // A rover is parked on the tile.
+    RoverInTheWay {
+        position: Position,
+        kind: RoverKind,
+    },
+    // Nobody has seen the ground there yet.
+    Unexplored {
+        position: Position,
+    },
+    // Mines have to be placed on a resource deposit.
+    MissingDeposit {
+        group: StructureGroup,
+    },
+    // The structure cannot be built on this kind of tile.
+    NotAllowed {
+        group: StructureGroup,
+        flora: Flora,
+        is_resource: bool,
+    },
+    // The ground under the footprint is too steep.
+    TooSteep {
+        group: StructureGroup,
+        slope: f64,
+    },
+    // No way over open ground or roads leads there from a base.
+    Unreachable {
+        group: StructureGroup,
+        position: Position,
+    },
+    // The colony cannot pay the construction cost.
+    InsufficientResources {
+        group: StructureGroup,
+        cost: HashMap<Resource, u64>,
+    },
+}
+
+impl Display for PlacementError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            PlacementError::Occupied {
+                position,
+                structure,
+            } => write!(f, "{} already exists at {}", structure, position),
+            PlacementError::OutOfBounds { group, footprint } => {
+                write!(f, "{} ({}) does not fit on the map here", group, footprint)
+            }
+            PlacementError::RoverInTheWay { position, kind } => {
+                write!(f, "{} is parked at {}", kind, position)
+            }
+            PlacementError::Unexplored { position } => {
+                write!(f, "{} is unexplored", position)
+            }
+            PlacementError::MissingDeposit { group } => {
+                write!(f, "{} must be placed on a resource deposit", group)
+            }
+            PlacementError::NotAllowed {
+                group,
+                flora,
+                is_resource,
+            } => {
+                if *is_resource {
+                    write!(f, "{} cannot be placed on a {} deposit", group, flora)
+                } else {
+                    write!(f, "{} cannot be placed on {}", group, flora)
+                }
+            }
+            PlacementError::TooSteep { group, slope } => {
+                write!(f, "{} cannot be built on a slope of {:.2}", group, slope)
+            }
+            PlacementError::Unreachable { group, position } => {
+                write!(f, "{} at {} cannot be reached from a base", group, position)
+            }
+            PlacementError::InsufficientResources { group, cost } => {
+                write!(f, "{} requires {}", group, format_resources(cost))
+            }
+        }
+    }
+}
+
+impl Error for PlacementError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SurveyError {
+    Unexplored { position: Position },
+    NoDeposit { position: Position },
+    AlreadySurveyed { position: Position },
+    InProgress { position: Position },
+}
+
+impl Display for SurveyError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            SurveyError::Unexplored { position } => write!(f, "{} is unexplored", position),
+            SurveyError::NoDeposit { position } => write!(f, "no deposit at {}", position),
+            SurveyError::AlreadySurveyed { position } => {
+                write!(f, "deposit at {} is already surveyed", position)
+            }
+            SurveyError::InProgress { position } => {
+                write!(f, "deposit at {} is being surveyed", position)
+            }
+        }
+    }
+}
+
+impl Error for SurveyError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RetoolError {
+    NoStructure { position: Position },
+    NotRetoolable { structure: String },
+    SameRecipe { structure: String, recipe: String },
+    Retooling { structure: String },
+}
+
+impl Display for RetoolError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            RetoolError::NoStructure { position } => write!(f, "no structure at {}", position),
+            RetoolError::NotRetoolable { structure } => {
+                write!(f, "{} has no recipe to change", structure)
+            }
+            RetoolError::SameRecipe { structure, recipe } => {
+                write!(f, "{} already makes {}", structure, recipe)
+            }
+            RetoolError::Retooling { structure } => {
+                write!(f, "{} is still retooling", structure)
+            }
+        }
+    }
+}
+
+impl Error for RetoolError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RoverError {
+    NoBase {
+        position: Position,
+    },
+    NoRoom {
+        kind: RoverKind,
+    },
+    InsufficientResources {
+        kind: RoverKind,
+        cost: HashMap<Resource, u64>,
+    },
+    NoRover {
+        position: Position,
+    },
+    NoSelection,
+    Unreachable {
+        kind: RoverKind,
+        position: Position,
+    },
+    Occupied {
+        kind: RoverKind,
+        position: Position,
+    },
+}
+
+impl Display for RoverError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            RoverError::NoBase { position } => write!(f, "no base at {}", position),
+            RoverError::NoRoom { kind } => {
+                write!(f, "no free tile next to the base for a {}", kind)
+            }
+            RoverError::InsufficientResources { kind, cost } => {
+                write!(f, "{} requires {}", kind, format_resources(cost))
+            }
+            RoverError::NoRover { position } => write!(f, "no rover at {}", position),
+            RoverError::NoSelection => write!(f, "no rover selected"),
+            RoverError::Unreachable { kind, position } => {
+                write!(f, "{} cannot reach {}", kind, position)
+            }
+            RoverError::Occupied { kind, position } => {
+                write!(f, "{} is already parked at {}", kind, position)
+            }
+        }
+    }
+}
+
+impl Error for RoverError {}
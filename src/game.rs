@@ -3,18 +3,42 @@ use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Result};
 
 use worldgen::noise::perlin::PerlinNoise;
-use worldgen::noisemap::{NoiseMap, NoiseMapGenerator, Seed, Step};
+use worldgen::noisemap::{NoiseMap, NoiseMapGenerator, NoiseMapGeneratorBase, Seed, Step};
 use worldgen::world::tile::Constraint;
 use worldgen::world::tile::ConstraintType;
 use worldgen::world::{Size, Tile, World};
 
-use crate::structures::{Structure, StructureGroup};
+use crate::errors::PlacementError;
+use crate::pollution::{GRASS_DEGRADE_POLLUTION, MAX_POLLUTION, POLLUTION_DECAY, POLLUTION_SPREAD};
+use crate::structures::{
+    ProductionTrait, SightTrait, Structure, StructureGroup, StructureGroupTrait,
+};
+use crate::terraforming::{TerraformProject, TERRAFORM_RADIUS};
 use rand::prelude::SliceRandom;
 use rand::Rng;
+use std::f64::consts::TAU;
 use std::iter::FromIterator;
+use std::result::Result as StdResult;
+use std::str::FromStr;
+use std::time::Duration;
 
 type WorldCache = Vec<Vec<MapTile>>;
 
+// Vein noise value above which a tile holds a deposit.
+const VEIN_THRESHOLD: f64 = 0.7;
+
+// Rare resources need at least this many deposits within reach of the spawn.
+const MIN_RARE_DEPOSITS: usize = 3;
+
+// Deposits of every construction resource within reach of the spawn.
+const MIN_CONSTRUCTION_DEPOSITS: usize = 2;
+
+// Distance from the spawn, in tiles, that counts as within reach.
+const SPAWN_REACH: f64 = 20.0;
+
+// How far around the landing site is known before anything is built.
+const LANDING_SIGHT: u16 = 8;
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Position {
     pub x: i16,
@@ -33,6 +57,22 @@ impl Position {
     pub fn y(&mut self, y: i16) {
         self.y = y
     }
+
+    pub fn distance(&self, other: &Position) -> f64 {
+        let dx = (self.x - other.x) as f64;
+        let dy = (self.y - other.y) as f64;
+        return (dx * dx + dy * dy).sqrt();
+    }
+
+    // The four tiles sharing an edge with this one.
+    pub fn neighbours(&self) -> Vec<Position> {
+        return vec![
+            Position::new(self.x, self.y - 1),
+            Position::new(self.x + 1, self.y),
+            Position::new(self.x, self.y + 1),
+            Position::new(self.x - 1, self.y),
+        ];
+    }
 }
 
 impl Display for Position {
@@ -41,6 +81,45 @@ impl Display for Position {
     }
 }
 
+// Size of the tile area a structure covers, counted from its top-left tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Footprint {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Footprint {
+    pub fn new(width: u16, height: u16) -> Footprint {
+        return Footprint { width, height };
+    }
+
+    pub fn cells(&self, anchor: &Position) -> Vec<Position> {
+        let mut cells = Vec::new();
+
+        for dy in 0..self.height as i16 {
+            for dx in 0..self.width as i16 {
+                cells.push(Position::new(anchor.x + dx, anchor.y + dy));
+            }
+        }
+
+        return cells;
+    }
+
+    // The middle tile, leaning to the bottom-right when a side has no single middle.
+    pub fn center(&self, anchor: &Position) -> Position {
+        return Position::new(
+            anchor.x + (self.width / 2) as i16,
+            anchor.y + (self.height / 2) as i16,
+        );
+    }
+}
+
+impl Display for Footprint {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Resource {
     Iron,
@@ -93,17 +172,19 @@ impl Display for Commodity {
 pub struct ResourceFactory {}
 
 impl ResourceFactory {
-    fn random_resource(resources: &mut Vec<Resource>) -> Resource {
-        let mut rng = rand::thread_rng();
-        resources.shuffle(&mut rng);
-        resources.get(0).unwrap().clone()
-    }
-
     fn random_amount(from: u64, to: u64) -> u64 {
         let mut rng = rand::thread_rng();
         rng.gen_range(from..to)
     }
 
+    // Picks a deposit kind from the kind noise, the value barely changes across a cluster.
+    // Noise values bunch up around zero, wrapping them spreads the kinds evenly.
+    fn resource_by_noise(resources: &[Resource], value: f64) -> Resource {
+        let band = value.rem_euclid(1.0);
+        let index = (band * resources.len() as f64).floor() as usize;
+        resources[index.min(resources.len() - 1)]
+    }
+
     fn random_resource_amount(resource: Resource) -> u64 {
         match resource {
             Resource::Iron => Self::random_amount(10000, 25000),
@@ -121,14 +202,26 @@ pub struct ResourceDeposit {
     pub resource: Resource,
     pub amount: u64,
     pub available: u64,
+    // The amount is only known after the deposit was surveyed.
+    pub surveyed: bool,
+    // Rough range shown until then, it does not always contain the real amount.
+    pub estimate: (u64, u64),
 }
 
 impl ResourceDeposit {
     pub fn new(resource: Resource, amount: u64) -> ResourceDeposit {
+        let mut rng = rand::thread_rng();
+
+        // A guess off by up to a third, with a spread of a quarter either way.
+        let guess = amount as f64 * rng.gen_range(0.67..1.33);
+        let estimate = ((guess * 0.75) as u64, (guess * 1.25) as u64);
+
         return ResourceDeposit {
             resource,
             amount,
             available: amount,
+            surveyed: false,
+            estimate,
         };
     }
 }
@@ -140,6 +233,11 @@ pub enum Flora {
     Dirt,
     Grass,
     Rock,
+    Ice,
+    Lava,
+    Crater,
+    SaltFlat,
+    ToxicSwamp,
 }
 
 impl Display for Flora {
@@ -148,10 +246,321 @@ impl Display for Flora {
     }
 }
 
+// One band of the world noise, from the previous layer up to `below`.
+pub struct TerrainLayer {
+    pub flora: Flora,
+    pub below: f64,
+}
+
+impl TerrainLayer {
+    pub fn new(flora: Flora, below: f64) -> TerrainLayer {
+        return TerrainLayer { flora, below };
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlanetType {
+    Temperate,
+    Frozen,
+    Desert,
+    Volcanic,
+    Ocean,
+}
+
+impl Display for PlanetType {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for PlanetType {
+    type Err = String;
+
+    fn from_str(name: &str) -> StdResult<PlanetType, String> {
+        match name.to_lowercase().as_str() {
+            "temperate" => Ok(PlanetType::Temperate),
+            "frozen" => Ok(PlanetType::Frozen),
+            "desert" => Ok(PlanetType::Desert),
+            "volcanic" => Ok(PlanetType::Volcanic),
+            "ocean" => Ok(PlanetType::Ocean),
+            _ => Err(format!("unknown planet type '{}'", name)),
+        }
+    }
+}
+
+impl PlanetType {
+    // Terrain layers ordered from the lowest noise value up,
+    // the last layer takes everything above the previous one.
+    pub fn layers(&self) -> Vec<TerrainLayer> {
+        match self {
+            PlanetType::Temperate => vec![
+                TerrainLayer::new(Flora::Water, -0.25),
+                TerrainLayer::new(Flora::Sand, 0.0),
+                TerrainLayer::new(Flora::Grass, 0.45),
+                TerrainLayer::new(Flora::Dirt, 0.85),
+                TerrainLayer::new(Flora::Rock, f64::MAX),
+            ],
+            PlanetType::Frozen => vec![
+                TerrainLayer::new(Flora::Water, -0.45),
+                TerrainLayer::new(Flora::Ice, 0.1),
+                TerrainLayer::new(Flora::Dirt, 0.5),
+                TerrainLayer::new(Flora::Crater, 0.65),
+                TerrainLayer::new(Flora::Rock, f64::MAX),
+            ],
+            PlanetType::Desert => vec![
+                TerrainLayer::new(Flora::Crater, -0.5),
+                TerrainLayer::new(Flora::SaltFlat, -0.2),
+                TerrainLayer::new(Flora::Sand, 0.5),
+                TerrainLayer::new(Flora::Dirt, 0.7),
+                TerrainLayer::new(Flora::Rock, f64::MAX),
+            ],
+            PlanetType::Volcanic => vec![
+                TerrainLayer::new(Flora::Lava, -0.3),
+                TerrainLayer::new(Flora::Rock, 0.2),
+                TerrainLayer::new(Flora::Crater, 0.5),
+                TerrainLayer::new(Flora::ToxicSwamp, 0.65),
+                TerrainLayer::new(Flora::Dirt, f64::MAX),
+            ],
+            PlanetType::Ocean => vec![
+                TerrainLayer::new(Flora::Water, 0.3),
+                TerrainLayer::new(Flora::ToxicSwamp, 0.45),
+                TerrainLayer::new(Flora::Sand, 0.6),
+                TerrainLayer::new(Flora::Grass, f64::MAX),
+            ],
+        }
+    }
+
+    pub fn climate(&self) -> Climate {
+        match self {
+            PlanetType::Temperate => Climate {
+                temperature: 22.0,
+                latitude_span: 40.0,
+                pressure: 85.0,
+                oxygen: 18.0,
+            },
+            PlanetType::Frozen => Climate {
+                temperature: -30.0,
+                latitude_span: 35.0,
+                pressure: 60.0,
+                oxygen: 4.0,
+            },
+            PlanetType::Desert => Climate {
+                temperature: 48.0,
+                latitude_span: 30.0,
+                pressure: 40.0,
+                oxygen: 1.0,
+            },
+            PlanetType::Volcanic => Climate {
+                temperature: 75.0,
+                latitude_span: 25.0,
+                pressure: 180.0,
+                oxygen: 0.0,
+            },
+            PlanetType::Ocean => Climate {
+                temperature: 26.0,
+                latitude_span: 30.0,
+                pressure: 110.0,
+                oxygen: 9.0,
+            },
+        }
+    }
+
+    // Resources that can form a deposit on a tile of this flora.
+    pub fn deposits(&self, flora: &Flora) -> Vec<Resource> {
+        match (self, flora) {
+            (PlanetType::Frozen, Flora::Dirt) => {
+                vec![Resource::Iron, Resource::Aluminum, Resource::Carbon]
+            }
+            (PlanetType::Frozen, Flora::Crater) => {
+                vec![Resource::Iron, Resource::Silica, Resource::Uranium]
+            }
+            (PlanetType::Volcanic, Flora::Rock) => vec![Resource::Iron, Resource::Uranium],
+            (PlanetType::Volcanic, Flora::Crater) => {
+                vec![Resource::Iron, Resource::Aluminum, Resource::Silica]
+            }
+            (PlanetType::Desert, Flora::Sand) => vec![Resource::Silica, Resource::Aluminum],
+            (PlanetType::Desert, Flora::Dirt) => {
+                vec![Resource::Iron, Resource::Aluminum, Resource::Carbon]
+            }
+            (PlanetType::Ocean, Flora::Sand) => {
+                vec![Resource::Silica, Resource::Iron, Resource::Aluminum]
+            }
+            (PlanetType::Ocean, Flora::ToxicSwamp) => vec![Resource::Carbon, Resource::Uranium],
+            (_, Flora::Water) => vec![Resource::Water, Resource::Carbon],
+            (_, Flora::Sand) => vec![Resource::Silica],
+            (_, Flora::Dirt) => vec![Resource::Iron, Resource::Aluminum],
+            (_, Flora::Grass) => vec![Resource::Carbon, Resource::Water],
+            (_, Flora::Rock) => vec![Resource::Uranium],
+            (_, Flora::Ice) => vec![Resource::Water],
+            (_, Flora::Lava) => vec![Resource::Iron, Resource::Uranium],
+            (_, Flora::Crater) => vec![Resource::Iron, Resource::Aluminum, Resource::Uranium],
+            (_, Flora::SaltFlat) => vec![Resource::Silica, Resource::Water],
+            (_, Flora::ToxicSwamp) => vec![Resource::Carbon],
+        }
+    }
+}
+
+// Real seconds a planetary day takes.
+pub const DAY_LENGTH: f64 = 240.0;
+
+// Share of the day already gone when the colony lands, early in the morning.
+pub const LANDING_PHASE: f64 = 0.3;
+
+// Time of day on the planet, following the game clock.
+#[derive(Clone, Copy, Debug)]
+pub struct DayCycle {
+    day: u64,
+    // Share of the current day gone by, 0.0 is midnight and 0.5 is noon.
+    phase: f64,
+}
+
+impl DayCycle {
+    pub fn at(elapsed: Duration) -> DayCycle {
+        let days = elapsed.as_secs_f64() / DAY_LENGTH + LANDING_PHASE;
+
+        return DayCycle {
+            day: days.floor() as u64 + 1,
+            phase: days.fract(),
+        };
+    }
+
+    // Strength of the sun, nothing between dusk and dawn and full at noon.
+    pub fn sunlight(&self) -> f64 {
+        return (-(self.phase * TAU).cos()).max(0.0);
+    }
+
+    pub fn is_night(&self) -> bool {
+        return self.sunlight() == 0.0;
+    }
+
+    // How bright the map is drawn, never fully dark so the terrain stays readable.
+    pub fn light(&self) -> f64 {
+        return 0.35 + 0.65 * self.sunlight();
+    }
+}
+
+impl Display for DayCycle {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let minutes = (self.phase * 24.0 * 60.0) as u64;
+        let period = if self.is_night() { "night" } else { "day" };
+
+        write!(
+            f,
+            "Day {}, {:02}:{:02} ({})",
+            self.day,
+            minutes / 60,
+            minutes % 60,
+            period
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct MapTile {
     pub flora: Flora,
     pub is_resource: bool,
+    // Terrain height from the world noise, roughly in the range of -1.0 to 1.0.
+    pub height: f64,
+    // Once seen by a structure, a tile stays explored for the rest of the game.
+    pub explored: bool,
+    // Surface temperature in degrees Celsius.
+    pub temperature: f64,
+    // Surface air pressure in kPa.
+    pub pressure: f64,
+    // Share of oxygen in the air, in percent.
+    pub oxygen: f64,
+    // Pollution left behind by structures, from 0.0 up to MAX_POLLUTION.
+    pub pollution: f64,
+}
+
+impl MapTile {
+    // Elevation in meters above the planet datum, used for display.
+    pub fn elevation(&self) -> i64 {
+        return (self.height * 1000.0).round() as i64;
+    }
+
+    // What still keeps people from living outside here, None once the tile is habitable.
+    pub fn hostility(&self) -> Option<&'static str> {
+        if self.temperature < COMFORT_MIN {
+            return Some("too cold");
+        }
+
+        if self.temperature > COMFORT_MAX {
+            return Some("too hot");
+        }
+
+        if self.pressure < BREATHABLE_PRESSURE.0 {
+            return Some("air too thin");
+        }
+
+        if self.pressure > BREATHABLE_PRESSURE.1 {
+            return Some("air too dense");
+        }
+
+        if self.oxygen < BREATHABLE_OXYGEN {
+            return Some("not enough oxygen");
+        }
+
+        return None;
+    }
+
+    // Moves the air a terraforming step towards what people can live in, never past it.
+    pub fn temper(&mut self, project: &TerraformProject) {
+        let step = project.temperature_step();
+        if self.temperature < COMFORT_MIN {
+            self.temperature = (self.temperature + step).min(COMFORT_MIN);
+        } else if self.temperature > COMFORT_MAX {
+            self.temperature = (self.temperature - step).max(COMFORT_MAX);
+        }
+
+        let step = project.pressure_step();
+        if self.pressure < BREATHABLE_PRESSURE.0 {
+            self.pressure = (self.pressure + step).min(BREATHABLE_PRESSURE.0);
+        } else if self.pressure > BREATHABLE_PRESSURE.1 {
+            self.pressure = (self.pressure - step).max(BREATHABLE_PRESSURE.1);
+        }
+
+        if self.oxygen < BREATHABLE_OXYGEN {
+            self.oxygen = (self.oxygen + project.oxygen_step()).min(BREATHABLE_OXYGEN);
+        }
+    }
+
+    // Extra energy structures on this tile spend heating or cooling, in percent.
+    pub fn climate_load(&self) -> u64 {
+        let degrees = if self.temperature < COMFORT_MIN {
+            COMFORT_MIN - self.temperature
+        } else if self.temperature > COMFORT_MAX {
+            self.temperature - COMFORT_MAX
+        } else {
+            0.0
+        };
+
+        return (degrees * CLIMATE_LOAD_PER_DEGREE)
+            .min(MAX_CLIMATE_LOAD)
+            .round() as u64;
+    }
+}
+
+// Temperatures equipment and people are comfortable in, in degrees Celsius.
+pub const COMFORT_MIN: f64 = 0.0;
+pub const COMFORT_MAX: f64 = 30.0;
+
+// Extra energy draw for every degree outside the comfortable range, in percent.
+pub const CLIMATE_LOAD_PER_DEGREE: f64 = 1.5;
+pub const MAX_CLIMATE_LOAD: f64 = 150.0;
+
+// Air pressure range people can breathe in, in kPa, and the oxygen share they need.
+pub const BREATHABLE_PRESSURE: (f64, f64) = (50.0, 150.0);
+pub const BREATHABLE_OXYGEN: f64 = 16.0;
+
+// Average conditions a planet type starts with, the map adds latitude, height and noise.
+pub struct Climate {
+    // Temperature at the equator on the datum.
+    pub temperature: f64,
+    // How much colder the poles are than the equator.
+    pub latitude_span: f64,
+    pub pressure: f64,
+    pub oxygen: f64,
 }
 
 pub struct MapObject {
@@ -161,20 +570,42 @@ pub struct MapObject {
 
 pub struct ObjectManager {
     objects: HashMap<Position, MapObject>,
+    // Every tile covered by a structure, pointing to the position the structure is kept at.
+    footprints: HashMap<Position, Position>,
 }
 
 impl ObjectManager {
     pub fn new() -> ObjectManager {
         let objects = HashMap::new();
-        ObjectManager { objects }
+        let footprints = HashMap::new();
+        ObjectManager {
+            objects,
+            footprints,
+        }
     }
 
-    pub fn contains(&self, position: &Position) -> bool {
-        self.objects.contains_key(position)
+    pub fn owner(&self, position: &Position) -> Option<&Position> {
+        self.footprints.get(position)
+    }
+
+    // Returns the object a tile belongs to, following structure footprints.
+    pub fn resolve(&self, position: &Position) -> Option<&MapObject> {
+        match self.footprints.get(position) {
+            Some(owner) => self.objects.get(owner),
+            None => self.objects.get(position),
+        }
+    }
+
+    pub fn occupy(&mut self, anchor: &Position, footprint: &Footprint) {
+        for cell in footprint.cells(anchor) {
+            self.footprints.insert(cell, anchor.clone());
+        }
     }
 
-    pub fn get(&self, position: &Position) -> Option<&MapObject> {
-        self.objects.get(position)
+    pub fn vacate(&mut self, anchor: &Position, footprint: &Footprint) {
+        for cell in footprint.cells(anchor) {
+            self.footprints.remove(&cell);
+        }
     }
 
     pub fn get_mut(&mut self, position: &Position) -> Option<&mut MapObject> {
@@ -202,7 +633,16 @@ pub struct TileFactory {}
 
 impl TileFactory {
     pub fn tile(flora: Flora, is_resource: bool, constraints: Vec<Constraint>) -> Tile<MapTile> {
-        let mut tile = Tile::new(MapTile { flora, is_resource });
+        let mut tile = Tile::new(MapTile {
+            flora,
+            is_resource,
+            height: 0.0,
+            explored: false,
+            temperature: 0.0,
+            pressure: 0.0,
+            oxygen: 0.0,
+            pollution: 0.0,
+        });
 
         for constraint in constraints {
             tile = tile.when(constraint);
@@ -215,12 +655,13 @@ impl TileFactory {
 pub struct GameMap {
     width: u16,
     height: u16,
+    planet: PlanetType,
     world: World<MapTile>,
     cache: WorldCache,
 }
 
 impl GameMap {
-    pub fn new(width: u16, height: u16) -> GameMap {
+    pub fn new(width: u16, height: u16, planet: PlanetType) -> GameMap {
         let noise = PerlinNoise::new();
 
         let nm1 = NoiseMap::new(noise)
@@ -233,87 +674,57 @@ impl GameMap {
 
         let nm = Box::new(nm1 + (nm2 * 4));
 
-        // Water
-        let water_tile =
-            TileFactory::tile(Flora::Water, false, vec![constraint!(nm.clone(), < -0.25)]);
-
-        let water_deposit_tile = TileFactory::tile(
-            Flora::Water,
-            true,
-            vec![
-                constraint!(nm.clone(), < -0.57),
-                constraint!(nm.clone(), > -0.6),
-            ],
-        );
-
-        // Sand
-        let sand_tile = TileFactory::tile(Flora::Sand, false, vec![constraint!(nm.clone(), < 0.0)]);
-
-        let sand_deposit_tile = TileFactory::tile(
-            Flora::Sand,
-            true,
-            vec![
-                constraint!(nm.clone(), < -0.05),
-                constraint!(nm.clone(), > -0.07),
-            ],
-        );
-
-        // Grass
-        let grass_tile =
-            TileFactory::tile(Flora::Grass, false, vec![constraint!(nm.clone(), < 0.45)]);
-
-        let grass_deposit_tile = TileFactory::tile(
-            Flora::Grass,
-            true,
-            vec![
-                constraint!(nm.clone(), < 0.3),
-                constraint!(nm.clone(), > 0.27),
-            ],
-        );
+        let mut world = World::new().set(Size::of(width as i64, height as i64));
 
-        // Mountains
-        let rock_tile =
-            TileFactory::tile(Flora::Rock, false, vec![constraint!(nm.clone(), > 0.85)]);
+        for layer in planet.layers() {
+            let constraints = if layer.below < f64::MAX {
+                vec![constraint!(nm.clone(), < layer.below)]
+            } else {
+                vec![]
+            };
 
-        let rock_deposit_tile = TileFactory::tile(
-            Flora::Rock,
-            true,
-            vec![
-                constraint!(nm.clone(), < 0.88),
-                constraint!(nm.clone(), > 0.86),
-            ],
-        );
+            world = world.add(TileFactory::tile(layer.flora, false, constraints));
+        }
 
-        // Hills
-        let dirt_tile = TileFactory::tile(Flora::Dirt, false, vec![]);
+        let mut cache = world.generate(0, 0).unwrap();
 
-        let dirt_deposit_tile = TileFactory::tile(
-            Flora::Dirt,
-            true,
-            vec![
-                constraint!(nm.clone(), < 0.6),
-                constraint!(nm.clone(), > 0.56),
-            ],
-        );
+        // The world only keeps the tile picked by the constraints, so the height
+        // field is sampled again from the same noise to keep it on every tile.
+        let heights = nm.generate_sized_chunk(Size::of(width as i64, height as i64), 0, 0);
+        for (row, height_row) in cache.iter_mut().zip(heights.iter()) {
+            for (tile, height) in row.iter_mut().zip(height_row.iter()) {
+                tile.height = *height;
+            }
+        }
 
-        let world = World::new()
-            .set(Size::of(width as i64, height as i64))
-            .add(water_deposit_tile)
-            .add(water_tile)
-            .add(sand_deposit_tile)
-            .add(sand_tile)
-            .add(grass_deposit_tile)
-            .add(grass_tile)
-            .add(rock_deposit_tile)
-            .add(rock_tile)
-            .add(dirt_deposit_tile)
-            .add(dirt_tile);
-
-        let cache = world.generate(0, 0).unwrap();
+        // Weather noise is smoother than the terrain, so climate changes over larger areas.
+        let weather = NoiseMap::new(noise)
+            .set_seed(Seed::of("MooFoo?"))
+            .set_step(Step::of(0.02, 0.02));
+        let weather = weather.generate_sized_chunk(Size::of(width as i64, height as i64), 0, 0);
+
+        let climate = planet.climate();
+        let equator = height as f64 / 2.0;
+
+        for (y, (row, weather_row)) in cache.iter_mut().zip(weather.iter()).enumerate() {
+            // 0.0 on the equator, 1.0 on either pole.
+            let latitude = ((y as f64 + 0.5 - equator) / equator).abs();
+
+            for (tile, noise) in row.iter_mut().zip(weather_row.iter()) {
+                // Air gets colder and thinner higher up.
+                tile.temperature = climate.temperature - climate.latitude_span * latitude
+                    + noise * 6.0
+                    - tile.height.max(0.0) * 15.0;
+                tile.pressure =
+                    (climate.pressure * (1.0 - tile.height * 0.2) + noise * 5.0).max(0.0);
+                tile.oxygen = (climate.oxygen + noise).max(0.0);
+            }
+        }
 
         return GameMap {
             width,
             height,
+            planet,
             world,
             cache,
         };
@@ -331,6 +742,10 @@ impl GameMap {
         return WorldCache::from_iter(self.cache.iter().cloned());
     }
 
+    pub fn planet(&self) -> PlanetType {
+        return self.planet;
+    }
+
     pub fn width(&self) -> u16 {
         return self.width;
     }
@@ -338,21 +753,52 @@ impl GameMap {
     pub fn height(&self) -> u16 {
         return self.height;
     }
+
+    // The steepest height difference between a tile and its direct neighbours.
+    pub fn slope(&self, position: &Position) -> f64 {
+        let height = self.cache[position.y as usize][position.x as usize].height;
+
+        let neighbours = [
+            Position::new(position.x - 1, position.y),
+            Position::new(position.x + 1, position.y),
+            Position::new(position.x, position.y - 1),
+            Position::new(position.x, position.y + 1),
+        ];
+
+        return neighbours
+            .iter()
+            .filter(|neighbour| self.contains(neighbour))
+            .map(|n| (self.cache[n.y as usize][n.x as usize].height - height).abs())
+            .fold(0.0, f64::max);
+    }
+
+    pub fn contains(&self, position: &Position) -> bool {
+        return position.x >= 0
+            && position.y >= 0
+            && (position.x as u16) < self.width
+            && (position.y as u16) < self.height;
+    }
 }
 
 pub struct MapController {
     map: GameMap,
     objects: ObjectManager,
     position: Position,
+    spawn: Position,
     locations: HashMap<Position, StructureGroup>,
+    // Bumped whenever structures come or go or the ground changes under them.
+    revision: u64,
 }
 
 impl MapController {
-    pub fn new(size: Size) -> MapController {
-        let position = Position::new(0, 0);
+    pub fn new(size: Size, planet: PlanetType) -> MapController {
         let (w, h) = (size.w as u16, size.h as u16);
 
-        let map = GameMap::new(w, h);
+        // The colony lands in the middle of the map, the cursor starts there too.
+        let spawn = Position::new((w / 2) as i16, (h / 2) as i16);
+        let position = spawn.clone();
+
+        let map = GameMap::new(w, h, planet);
 
         let objects = ObjectManager::new();
 
@@ -362,7 +808,9 @@ impl MapController {
             map,
             objects,
             position,
+            spawn,
             locations,
+            revision: 0,
         };
     }
 
@@ -374,6 +822,11 @@ impl MapController {
         return &self.locations;
     }
 
+    // Changes whenever something a route could depend on does, so routes can be kept until then.
+    pub fn revision(&self) -> u64 {
+        return self.revision;
+    }
+
     pub fn objects(&self) -> &ObjectManager {
         return &self.objects;
     }
@@ -391,17 +844,288 @@ impl MapController {
     }
 
     pub fn object(&self) -> Option<&MapObject> {
-        return self.objects.get(&self.position);
+        return self.objects.resolve(&self.position);
     }
 
     pub fn object_at(&self, position: &Position) -> Option<&MapObject> {
-        return self.objects.get(position);
+        return self.objects.resolve(position);
     }
 
     pub fn position(&self) -> Position {
         return self.position.clone();
     }
 
+    pub fn spawn(&self) -> Position {
+        return self.spawn.clone();
+    }
+
+    // Switches the structure anchored at a position on or off, returns whether one was found.
+    pub fn set_enabled(&mut self, position: &Position, enabled: bool) -> bool {
+        let structure = self
+            .objects
+            .get_mut(position)
+            .and_then(|o| o.structure.as_mut());
+
+        return match structure {
+            Some(structure) => {
+                structure.blueprint_mut().set_enabled(enabled);
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn pollute(&mut self, position: &Position, amount: f64) {
+        if self.map.contains(position) {
+            let tile = self.tile_at_mut(position.clone());
+            tile.pollution = (tile.pollution + amount).min(MAX_POLLUTION);
+        }
+    }
+
+    // Takes pollution out of every tile within the radius of a position.
+    pub fn scrub(&mut self, center: &Position, radius: f64, amount: f64) {
+        let reach = radius.ceil() as i16;
+
+        for y in center.y - reach..=center.y + reach {
+            for x in center.x - reach..=center.x + reach {
+                let position = Position::new(x, y);
+
+                if self.map.contains(&position) && center.distance(&position) <= radius {
+                    let tile = self.tile_at_mut(position);
+                    tile.pollution = (tile.pollution - amount).max(0.0);
+                }
+            }
+        }
+    }
+
+    // Lets pollution drift to neighbouring tiles and slowly fade, grass on badly
+    // polluted tiles dies back to dirt. Returns how many grass tiles were lost.
+    pub fn spread_pollution(&mut self) -> u64 {
+        let previous: Vec<Vec<f64>> = self
+            .map
+            .cache
+            .iter()
+            .map(|row| row.iter().map(|tile| tile.pollution).collect())
+            .collect();
+
+        let pollution_at = |position: &Position| -> f64 {
+            if position.x < 0 || position.y < 0 {
+                return 0.0;
+            }
+
+            return previous
+                .get(position.y as usize)
+                .and_then(|row| row.get(position.x as usize))
+                .copied()
+                .unwrap_or(0.0);
+        };
+
+        let mut degraded = 0;
+
+        for (y, row) in self.map.cache.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                let position = Position::new(x as i16, y as i16);
+
+                let inflow: f64 = position
+                    .neighbours()
+                    .iter()
+                    .map(|neighbour| pollution_at(neighbour) * POLLUTION_SPREAD / 4.0)
+                    .sum();
+                let kept = pollution_at(&position) * (1.0 - POLLUTION_SPREAD);
+
+                tile.pollution = ((kept + inflow) * (1.0 - POLLUTION_DECAY)).min(MAX_POLLUTION);
+
+                // Traces too small to matter are dropped, so clean ground reads as clean.
+                if tile.pollution < 0.01 {
+                    tile.pollution = 0.0;
+                }
+
+                if tile.flora == Flora::Grass && tile.pollution > GRASS_DEGRADE_POLLUTION {
+                    tile.flora = Flora::Dirt;
+                    degraded += 1;
+                }
+            }
+        }
+
+        if degraded > 0 {
+            self.revision += 1;
+        }
+
+        return degraded;
+    }
+
+    // Lets every structure know how hard it has to work against the climate under it.
+    pub fn update_climate(&mut self) {
+        for (anchor, object) in self.objects.list_mut() {
+            if let Some(structure) = object.structure.as_mut() {
+                let load = structure
+                    .group()
+                    .footprint()
+                    .cells(anchor)
+                    .iter()
+                    .filter(|cell| self.map.contains(cell))
+                    .map(|cell| self.map.cache[cell.y as usize][cell.x as usize].climate_load())
+                    .max()
+                    .unwrap_or(0);
+
+                structure.blueprint_mut().set_climate_load(load);
+            }
+        }
+    }
+
+    // Lets terraformers change the tiles of the cycles they finished, then points each
+    // at the next tile it will work on. Returns what changed, for the console.
+    pub fn terraform(&mut self) -> Vec<String> {
+        let mut terraformers = vec![];
+
+        for (anchor, object) in self.objects.list_mut() {
+            if let Some(Structure::Terraformer { structure }) = object.structure.as_mut() {
+                terraformers.push((
+                    anchor.clone(),
+                    structure.project(),
+                    structure.blueprint_mut().take_output(),
+                ));
+            }
+        }
+
+        let mut messages = vec![];
+
+        for (anchor, project, pending) in terraformers {
+            let reach = self.terraform_reach(&anchor);
+            let mut target = self.terraform_target(&reach, &project);
+
+            for _ in 0..pending {
+                let position = match target {
+                    Some(position) => position,
+                    None => break,
+                };
+
+                let tile = self.tile_at_mut(position.clone());
+                let before = tile.flora;
+
+                if let Some(after) = project.convert(&before) {
+                    tile.flora = after;
+                    self.revision += 1;
+                    messages.push(format!(
+                        "Terraformer at {} turned {} into {} at {}",
+                        anchor, before, after, position
+                    ));
+                }
+
+                // Every cycle also works on the air over the whole reach.
+                for cell in reach.iter() {
+                    self.tile_at_mut(cell.clone()).temper(&project);
+                }
+
+                target = self.terraform_target(&reach, &project);
+            }
+
+            if let Some(Structure::Terraformer { structure }) = self
+                .objects
+                .get_mut(&anchor)
+                .and_then(|o| o.structure.as_mut())
+            {
+                structure.set_target(target);
+            }
+        }
+
+        return messages;
+    }
+
+    // Tiles within reach of a terraformer footprint, closest first.
+    fn terraform_reach(&self, anchor: &Position) -> Vec<Position> {
+        let cells = StructureGroup::Terraformer.footprint().cells(anchor);
+        let reach = TERRAFORM_RADIUS.ceil() as i16 + 1;
+
+        let distance = |position: &Position| -> f64 {
+            return cells
+                .iter()
+                .map(|cell| cell.distance(position))
+                .fold(f64::MAX, f64::min);
+        };
+
+        let mut tiles = vec![];
+
+        for y in anchor.y - reach..=anchor.y + reach {
+            for x in anchor.x - reach..=anchor.x + reach {
+                let position = Position::new(x, y);
+                let d = distance(&position);
+
+                if self.map.contains(&position) && d <= TERRAFORM_RADIUS {
+                    tiles.push((d, position));
+                }
+            }
+        }
+
+        tiles.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        return tiles.into_iter().map(|(_, position)| position).collect();
+    }
+
+    // Closest explored tile in reach that the project can change, deposits and tiles
+    // under structures are left alone. Once all are done, the closest one with hostile air.
+    fn terraform_target(&self, reach: &[Position], project: &TerraformProject) -> Option<Position> {
+        let convertible = reach.iter().find(|position| {
+            let tile = self.tile_at(position);
+
+            return tile.explored
+                && !tile.is_resource
+                && self.object_at(position).is_none()
+                && project.convert(&tile.flora).is_some();
+        });
+
+        if convertible.is_some() {
+            return convertible.cloned();
+        }
+
+        return reach
+            .iter()
+            .find(|position| {
+                let tile = self.tile_at(position);
+                return tile.explored && tile.hostility().is_some();
+            })
+            .cloned();
+    }
+
+    // Share of the explored map people could live on without domes, in percent.
+    pub fn habitable_share(&self) -> f64 {
+        let explored: Vec<&MapTile> = self
+            .map
+            .cache
+            .iter()
+            .flatten()
+            .filter(|tile| tile.explored)
+            .collect();
+
+        if explored.is_empty() {
+            return 0.0;
+        }
+
+        let habitable = explored
+            .iter()
+            .filter(|tile| tile.hostility().is_none())
+            .count();
+
+        return habitable as f64 * 100.0 / explored.len() as f64;
+    }
+
+    // Every tile covered by a base, where routes and rovers start from.
+    pub fn base_cells(&self) -> Vec<Position> {
+        return self
+            .objects
+            .list()
+            .filter(|(_, o)| matches!(o.structure, Some(Structure::Base { .. })))
+            .flat_map(|(position, _)| StructureGroup::Base.footprint().cells(position))
+            .collect();
+    }
+
+    // Moves the cursor straight to a position, ignored when it is off the map.
+    pub fn jump(&mut self, position: &Position) {
+        if self.map.contains(position) {
+            self.position = position.clone();
+        }
+    }
+
     pub fn tile(&self) -> &MapTile {
         let x = self.position.x as usize;
         let y = self.position.y as usize;
@@ -423,6 +1147,16 @@ impl MapController {
         return &mut self.map.cache[y][x];
     }
 
+    // The steepest slope under a structure footprint placed at a position.
+    pub fn footprint_slope(&self, position: &Position, footprint: &Footprint) -> f64 {
+        return footprint
+            .cells(position)
+            .iter()
+            .filter(|cell| self.map.contains(cell))
+            .map(|cell| self.map.slope(cell))
+            .fold(0.0, f64::max);
+    }
+
     pub fn up(&mut self) {
         let y = self.position.y as u16;
         if y > 0 {
@@ -451,25 +1185,26 @@ impl MapController {
         }
     }
 
-    pub fn add_structure(&mut self, structure: Structure) -> Option<MapObject> {
+    pub fn add_structure(&mut self, structure: Structure) -> StdResult<(), PlacementError> {
         let position = self.position();
-
-        if self.objects.contains(&position) {
-            let mut object = self.remove_object(&position)?;
-
-            if object.structure.is_some() {
-                panic!(
-                    "trying add structure, but {} already exists at {}",
-                    &object.structure.unwrap(),
-                    &position
-                )
+        let footprint = structure.group().footprint();
+
+        for cell in footprint.cells(&position) {
+            if let Some(existing) = self.object_at(&cell).and_then(|o| o.structure.as_ref()) {
+                return Err(PlacementError::Occupied {
+                    position: cell,
+                    structure: existing.to_string(),
+                });
             }
+        }
 
-            // add deposit to object
-            object.structure = Option::from(structure);
+        self.objects.occupy(&position, &footprint);
+        self.revision += 1;
 
+        if let Some(object) = self.objects.get_mut(&position) {
             // add structure to existing object
-            return self.objects.set(position, object);
+            object.structure = Option::from(structure);
+            return Ok(());
         }
 
         let object = MapObject {
@@ -477,43 +1212,479 @@ impl MapController {
             deposit: Option::None,
         };
 
-        self.add_object(position, object)
+        self.add_object(position, object);
+        return Ok(());
     }
 
-    pub fn destroy_structure(&mut self) {
+    // The structure covering the cursor, following footprints to its anchor.
+    pub fn structure_mut(&mut self) -> Option<&mut Structure> {
+        let anchor = self.objects.owner(&self.position)?.clone();
+
+        return self
+            .objects
+            .get_mut(&anchor)
+            .and_then(|o| o.structure.as_mut());
+    }
+
+    // Removes the structure covering the cursor and hands it back for salvage.
+    // Deposits stay on the map, empty objects are dropped.
+    pub fn destroy_structure(&mut self) -> Option<Structure> {
         let position = self.position();
-        let mut object = self.remove_object(&position).unwrap();
-        object.structure = Option::None;
-        self.add_object(position, object);
+        let anchor = self.objects.owner(&position)?.clone();
+
+        let object = self.objects.get_mut(&anchor)?;
+        let structure = object.structure.take()?;
+
+        if object.deposit.is_none() {
+            self.remove_object(&anchor);
+        }
+
+        self.objects.vacate(&anchor, &structure.group().footprint());
+        self.revision += 1;
+
+        return Option::from(structure);
     }
 
+    // Deposits form where the vein noise peaks, so they come in clusters,
+    // and a second, slower noise keeps one resource kind across a cluster.
     pub fn generate_deposits(&mut self) {
         let cache = self.map().cache_copy();
+        let planet = self.map().planet();
+        let size = Size::of(self.map.width() as i64, self.map.height() as i64);
+
+        let veins = NoiseMap::new(PerlinNoise::new())
+            .set_seed(Seed::of("Veins"))
+            .set_step(Step::of(0.12, 0.12))
+            .generate_sized_chunk(size, 0, 0);
+
+        let kinds = NoiseMap::new(PerlinNoise::new())
+            .set_seed(Seed::of("Kinds"))
+            .set_step(Step::of(0.04, 0.04))
+            .generate_sized_chunk(size, 0, 0);
 
         for (y, row) in cache.iter().enumerate() {
             for (x, tile) in row.iter().enumerate() {
-                let mut deposits = match tile.flora {
-                    Flora::Water => vec![Resource::Water, Resource::Carbon],
-                    Flora::Sand => vec![Resource::Silica],
-                    Flora::Dirt => vec![Resource::Iron, Resource::Aluminum],
-                    Flora::Grass => vec![Resource::Carbon, Resource::Water],
-                    Flora::Rock => vec![Resource::Uranium],
-                };
+                let vein = veins[y][x];
+
+                if vein < VEIN_THRESHOLD {
+                    continue;
+                }
+
+                let position = Position::new(x as i16, y as i16);
+                let deposits = planet.deposits(&tile.flora);
+                let resource = ResourceFactory::resource_by_noise(&deposits, kinds[y][x]);
+
+                // Vein cores hold up to half again as much.
+                let core = (vein - VEIN_THRESHOLD) / (1.0 - VEIN_THRESHOLD);
+                let strength = 1.0 + core.min(1.0) * 0.5;
+                self.place_deposit(position, resource, strength);
+            }
+        }
+
+        self.ensure_supply(Resource::Uranium, MIN_RARE_DEPOSITS, SPAWN_REACH);
+
+        // Everything that is built costs these, no planet may go without them.
+        for resource in [
+            Resource::Iron,
+            Resource::Aluminum,
+            Resource::Silica,
+            Resource::Carbon,
+        ] {
+            self.ensure_supply(resource, MIN_CONSTRUCTION_DEPOSITS, SPAWN_REACH);
+        }
+    }
+
+    pub fn explore_landing(&mut self) {
+        self.reveal(&self.spawn(), LANDING_SIGHT, false);
+    }
+
+    // Reveals the map around every structure that can see, radars also survey deposits.
+    pub fn explore(&mut self) {
+        let sights: Vec<(Position, u16, bool)> = self
+            .objects
+            .list()
+            .filter_map(|(position, o)| o.structure.as_ref().map(|s| (position, s)))
+            .filter(|(_, s)| s.blueprint().is_enabled() && s.blueprint().active())
+            .map(|(position, s)| {
+                // Sight reaches out evenly from the middle of the footprint.
+                let center = s.group().footprint().center(position);
+                (center, s.blueprint().radius(), s.blueprint().surveys())
+            })
+            .collect();
+
+        for (center, radius, survey) in sights {
+            self.reveal(&center, radius, survey);
+        }
+    }
 
-                if tile.is_resource {
-                    let resource = ResourceFactory::random_resource(deposits.as_mut());
-                    let amount = ResourceFactory::random_resource_amount(resource);
-                    // TODO: make amount random in range
-                    let deposit = ResourceDeposit::new(resource, amount);
+    pub fn reveal(&mut self, center: &Position, radius: u16, survey: bool) {
+        let radius = radius as i16;
 
-                    let object = MapObject {
-                        structure: Option::None,
-                        deposit: Option::from(deposit),
-                    };
+        for y in center.y - radius..=center.y + radius {
+            for x in center.x - radius..=center.x + radius {
+                let position = Position::new(x, y);
 
-                    self.add_object(Position::new(x as i16, y as i16), object);
+                if !self.map.contains(&position) || center.distance(&position) > radius as f64 {
+                    continue;
+                }
+
+                self.tile_at_mut(position.clone()).explored = true;
+
+                if survey {
+                    if let Some(deposit) = self
+                        .objects
+                        .get_mut(&position)
+                        .and_then(|o| o.deposit.as_mut())
+                    {
+                        deposit.surveyed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    // Richness grows with distance from the spawn, from half to one and a half times.
+    fn richness(&self, position: &Position) -> f64 {
+        let max = self.spawn.distance(&Position::new(0, 0)).max(1.0);
+        return 0.5 + (self.spawn.distance(position) / max).min(1.0);
+    }
+
+    fn place_deposit(&mut self, position: Position, resource: Resource, strength: f64) {
+        let richness = self.richness(&position);
+        let amount = ResourceFactory::random_resource_amount(resource) as f64;
+        let deposit = ResourceDeposit::new(resource, (amount * richness * strength) as u64);
+
+        self.tile_at_mut(position.clone()).is_resource = true;
+
+        let object = MapObject {
+            structure: Option::None,
+            deposit: Option::from(deposit),
+        };
+
+        self.add_object(position, object);
+    }
+
+    // Makes sure a resource has enough deposits within reach of the spawn,
+    // seeding extra ones on open land if the generated map came up short.
+    // Flora the planet lets the resource form on comes first, any open land
+    // only when none of it lies within reach.
+    fn ensure_supply(&mut self, resource: Resource, count: usize, reach: f64) {
+        let found = self
+            .objects
+            .list()
+            .filter_map(|(position, o)| o.deposit.map(|d| (position, d)))
+            .filter(|(position, d)| {
+                d.resource == resource && self.spawn.distance(position) <= reach
+            })
+            .count();
+
+        if found >= count {
+            return;
+        }
+
+        let planet = self.map.planet();
+        let mut candidates = Vec::new();
+        let mut fallback = Vec::new();
+        for y in 0..self.map.height() as i16 {
+            for x in 0..self.map.width() as i16 {
+                let position = Position::new(x, y);
+                let distance = self.spawn.distance(&position);
+                let tile = self.tile_at(&position);
+
+                // Not right under the landing site, and not somewhere a mine cannot reach.
+                if distance < reach / 3.0 || distance > reach || tile.is_resource {
+                    continue;
+                }
+
+                if tile.flora == Flora::Water || tile.flora == Flora::Lava {
+                    continue;
+                }
+
+                if planet.deposits(&tile.flora).contains(&resource) {
+                    candidates.push(position);
+                } else {
+                    fallback.push(position);
                 }
             }
         }
+
+        if candidates.is_empty() {
+            candidates = fallback;
+        }
+
+        let mut rng = rand::thread_rng();
+        candidates.shuffle(&mut rng);
+
+        for position in candidates.into_iter().take(count - found) {
+            self.place_deposit(position, resource, 1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::{Base, StructureCostFactory, Terraformer};
+    use worldgen::world::Size;
+
+    #[test]
+    fn footprint_cells_start_at_the_anchor() {
+        let cells = Footprint::new(2, 3).cells(&Position::new(4, 7));
+
+        assert_eq!(cells.len(), 6);
+        assert_eq!(cells.first(), Some(&Position::new(4, 7)));
+        assert_eq!(cells.last(), Some(&Position::new(5, 9)));
+        assert!(cells.contains(&Position::new(5, 7)));
+        assert!(cells.contains(&Position::new(4, 9)));
+        assert!(!cells.contains(&Position::new(6, 7)));
+        assert!(!cells.contains(&Position::new(4, 10)));
+    }
+
+    #[test]
+    fn single_tile_footprint_is_the_anchor() {
+        let anchor = Position::new(-1, 0);
+
+        assert_eq!(Footprint::new(1, 1).cells(&anchor), vec![anchor]);
+    }
+
+    #[test]
+    fn footprints_block_every_covered_tile() {
+        let mut objects = ObjectManager::new();
+        let anchor = Position::new(2, 2);
+        objects.occupy(&anchor, &StructureGroup::Base.footprint());
+
+        for cell in StructureGroup::Base.footprint().cells(&anchor) {
+            assert_eq!(objects.owner(&cell), Some(&anchor));
+        }
+
+        assert_eq!(objects.owner(&Position::new(5, 2)), None);
+    }
+
+    #[test]
+    fn bases_see_as_far_on_every_side() {
+        let mut controller = MapController::new(Size::of(30, 30), PlanetType::Temperate);
+        controller.jump(&Position::new(10, 10));
+        controller
+            .add_structure(Structure::Base {
+                structure: Base::new(),
+            })
+            .unwrap();
+        controller.explore();
+
+        // The base covers 10 to 12 and sees six tiles out from 11.
+        let explored = |x: i16| controller.tile_at(&Position::new(x, 11)).explored;
+        assert!(explored(5) && explored(17));
+        assert!(!explored(4) && !explored(18));
+    }
+
+    // A small clean map covered in a single kind of flora.
+    fn ground(flora: Flora) -> MapController {
+        let mut controller = MapController::new(Size::of(5, 5), PlanetType::Temperate);
+
+        for y in 0..5 {
+            for x in 0..5 {
+                let tile = controller.tile_at_mut(Position::new(x, y));
+                tile.flora = flora;
+                tile.pollution = 0.0;
+            }
+        }
+
+        return controller;
+    }
+
+    #[test]
+    fn guaranteed_deposits_only_form_where_the_planet_allows() {
+        let mut controller = ground(Flora::Sand);
+        for y in 0..5 {
+            controller.tile_at_mut(Position::new(4, y)).flora = Flora::Dirt;
+        }
+
+        // Temperate sand only ever holds silica, iron has to go on the dirt.
+        controller.ensure_supply(Resource::Silica, 3, 3.0);
+        controller.ensure_supply(Resource::Iron, 3, 3.0);
+
+        let deposits: Vec<_> = controller
+            .objects()
+            .list()
+            .filter_map(|(position, o)| o.deposit.map(|d| (position.clone(), d.resource)))
+            .collect();
+
+        assert_eq!(deposits.len(), 6);
+        for (position, resource) in deposits {
+            let flora = controller.tile_at(&position).flora;
+            assert!(PlanetType::Temperate.deposits(&flora).contains(&resource));
+        }
+    }
+
+    #[test]
+    fn guaranteed_deposits_fall_back_to_open_land() {
+        let mut controller = ground(Flora::Sand);
+        controller.ensure_supply(Resource::Iron, 3, 3.0);
+
+        let iron = controller
+            .objects()
+            .list()
+            .filter(|(_, o)| o.deposit.is_some_and(|d| d.resource == Resource::Iron))
+            .count();
+        assert_eq!(iron, 3);
+    }
+
+    #[test]
+    fn every_planet_can_pay_for_every_structure_near_the_spawn() {
+        let groups = [
+            StructureGroup::Base,
+            StructureGroup::Power,
+            StructureGroup::Solar,
+            StructureGroup::Mine,
+            StructureGroup::Refinery,
+            StructureGroup::Factory,
+            StructureGroup::Storage,
+            StructureGroup::Radar,
+            StructureGroup::Road,
+            StructureGroup::Shield,
+            StructureGroup::Terraformer,
+            StructureGroup::Scrubber,
+        ];
+
+        for planet in [
+            PlanetType::Temperate,
+            PlanetType::Frozen,
+            PlanetType::Desert,
+            PlanetType::Volcanic,
+            PlanetType::Ocean,
+        ] {
+            let mut controller = MapController::new(Size::of(90, 40), planet);
+            controller.generate_deposits();
+            let spawn = controller.spawn();
+
+            for group in groups.iter() {
+                for resource in StructureCostFactory::cost_for_structure(group).keys() {
+                    let found = controller.objects().list().any(|(position, o)| {
+                        o.deposit.is_some_and(|d| d.resource == *resource)
+                            && spawn.distance(position) <= SPAWN_REACH
+                    });
+                    assert!(found, "{} has no {} for a {}", planet, resource, group);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pollution_spreads_to_the_four_neighbours_and_fades() {
+        let mut controller = ground(Flora::Dirt);
+        let center = Position::new(2, 2);
+        controller.pollute(&center, 40.0);
+
+        assert_eq!(controller.spread_pollution(), 0);
+
+        let kept = 40.0 * (1.0 - POLLUTION_SPREAD) * (1.0 - POLLUTION_DECAY);
+        let passed = 40.0 * POLLUTION_SPREAD / 4.0 * (1.0 - POLLUTION_DECAY);
+        assert_eq!(controller.tile_at(&center).pollution, kept);
+
+        for neighbour in center.neighbours() {
+            assert_eq!(controller.tile_at(&neighbour).pollution, passed);
+        }
+
+        assert_eq!(controller.tile_at(&Position::new(1, 1)).pollution, 0.0);
+        assert_eq!(controller.tile_at(&Position::new(2, 0)).pollution, 0.0);
+    }
+
+    #[test]
+    fn badly_polluted_grass_dies_back_to_dirt() {
+        let mut controller = ground(Flora::Grass);
+        controller.pollute(&Position::new(2, 2), GRASS_DEGRADE_POLLUTION * 2.0);
+
+        assert_eq!(controller.spread_pollution(), 1);
+        assert_eq!(controller.tile_at(&Position::new(2, 2)).flora, Flora::Dirt);
+        assert_eq!(controller.tile_at(&Position::new(2, 1)).flora, Flora::Grass);
+    }
+
+    #[test]
+    fn traces_of_pollution_fade_away() {
+        let mut controller = ground(Flora::Dirt);
+        controller.pollute(&Position::new(0, 0), 0.01);
+
+        controller.spread_pollution();
+
+        assert_eq!(controller.tile_at(&Position::new(0, 0)).pollution, 0.0);
+    }
+
+    #[test]
+    fn scrubbers_clean_only_within_their_radius() {
+        let mut controller = ground(Flora::Dirt);
+        for y in 0..5 {
+            for x in 0..5 {
+                controller.pollute(&Position::new(x, y), 10.0);
+            }
+        }
+
+        controller.scrub(&Position::new(2, 2), 1.0, 4.0);
+        controller.scrub(&Position::new(0, 0), 1.0, 40.0);
+
+        assert_eq!(controller.tile_at(&Position::new(2, 2)).pollution, 6.0);
+        assert_eq!(controller.tile_at(&Position::new(3, 2)).pollution, 6.0);
+        assert_eq!(controller.tile_at(&Position::new(3, 3)).pollution, 10.0);
+        assert_eq!(controller.tile_at(&Position::new(0, 0)).pollution, 0.0);
+    }
+
+    #[test]
+    fn tempering_moves_the_air_towards_breathable_and_stops_there() {
+        let mut controller = MapController::new(Size::of(4, 4), PlanetType::Volcanic);
+        let tile = controller.tile_at_mut(Position::new(0, 0));
+        tile.temperature = -10.0;
+        tile.pressure = 150.25;
+        tile.oxygen = 15.75;
+
+        tile.temper(&TerraformProject::Greening);
+
+        assert_eq!(tile.temperature, -9.75);
+        assert_eq!(tile.pressure, BREATHABLE_PRESSURE.1);
+        assert_eq!(tile.oxygen, BREATHABLE_OXYGEN);
+        assert_eq!(tile.hostility(), Some("too cold"));
+    }
+
+    #[test]
+    fn finished_cycles_thicken_the_air_in_reach() {
+        let mut controller = MapController::new(Size::of(20, 20), PlanetType::Desert);
+
+        for y in 0..20 {
+            for x in 0..20 {
+                let tile = controller.tile_at_mut(Position::new(x, y));
+                tile.flora = Flora::Grass;
+                tile.is_resource = false;
+                tile.explored = true;
+                tile.temperature = 20.0;
+                tile.pressure = 100.0;
+                tile.oxygen = 1.0;
+            }
+        }
+
+        let anchor = Position::new(2, 2);
+        let mut terraformer = Terraformer::new(TerraformProject::Greening);
+        let blueprint = terraformer.blueprint_mut();
+        blueprint.start_cycle(&HashMap::new(), &HashMap::new());
+        while !blueprint.advance() {}
+
+        controller.jump(&anchor);
+        controller
+            .add_structure(Structure::Terraformer {
+                structure: terraformer,
+            })
+            .unwrap();
+
+        // Nothing to grow on grass, the cycle only works on the air.
+        assert!(controller.terraform().is_empty());
+
+        let step = TerraformProject::Greening.oxygen_step();
+        assert_eq!(controller.tile_at(&Position::new(0, 0)).oxygen, 1.0 + step);
+        assert_eq!(controller.tile_at(&Position::new(8, 2)).oxygen, 1.0 + step);
+        assert_eq!(controller.tile_at(&Position::new(9, 2)).oxygen, 1.0);
+        assert_eq!(controller.tile_at(&Position::new(19, 19)).oxygen, 1.0);
+
+        let target = match controller.object_at(&anchor).unwrap().structure.as_ref() {
+            Some(Structure::Terraformer { structure }) => structure.target().cloned(),
+            _ => None,
+        };
+        assert!(target.is_some());
     }
 }
@@ -1,19 +1,40 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::iter::FromIterator;
 use std::ops::Neg;
 use std::time::Duration;
 
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Style};
+use tui::symbols::Marker;
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, BorderType, Borders, List, ListItem, Paragraph, Wrap};
+use tui::widgets::{
+    Axis, Block, BorderType, Borders, Cell, Chart, Dataset, GraphType, List, ListItem, Paragraph,
+    Row, Sparkline, Table, Wrap,
+};
 
 use crate::game::{
-    Commodity, Flora, GameMap, Manufactured, MapObject, MapTile, ObjectManager, Position, Resource,
+    Commodity, DayCycle, Flora, GameMap, Manufactured, MapObject, MapTile, ObjectManager, Position,
+    Resource, ResourceDeposit, BREATHABLE_OXYGEN, BREATHABLE_PRESSURE,
 };
 
-use crate::managers::{EnergyManager, ResourceManager};
+use crate::component::{ComponentGroup, ComponentName};
+use crate::errors::PlacementError;
+use crate::managers::{
+    Bottleneck, BottleneckEntry, BottleneckManager, EnergyManager, HazardManager, Metric,
+    PollutionManager, ResourceManager, Route, RoverManager, StatisticsManager,
+};
+use crate::pollution::{
+    PollutionLevel, GRASS_DEGRADE_POLLUTION, HEALTHY_POLLUTION, SCRUBBER_RADIUS,
+};
+use crate::rovers::{Rover, RoverKind, RoverStatus};
 use crate::structures::{
-    BatteryTrait, EnergyTrait, ResourceStorageTrait, Structure, StructureBlueprint, StructureGroup,
+    BatteryTrait, CommodityStorageTrait, EnergyTrait, MineOutputTrait, ProductionTrait,
+    ResourceStorageTrait, SightTrait, Structure, StructureBlueprint, StructureGroup,
+    StructureGroupTrait, StructureStatus, Terraformer, OUTPUT_BUFFER_CAPACITY, SHIELD_RADIUS,
 };
+use crate::terraforming::{TerraformProject, TERRAFORM_CYCLE_TICKS, TERRAFORM_RADIUS};
+use crate::util::{format_resources, UPDATE_RATE_MS};
 use itertools::Itertools;
 
 #[derive(Clone, Copy)]
@@ -39,6 +60,125 @@ impl From<BlockType> for char {
     }
 }
 
+// The structure selected in the build menu, as it would be placed under the cursor.
+pub struct PlacementPreview {
+    pub group: StructureGroup,
+    pub placement: Result<(), PlacementError>,
+    pub cost: HashMap<Resource, u64>,
+}
+
+impl PlacementPreview {
+    pub fn new(
+        group: StructureGroup,
+        placement: Result<(), PlacementError>,
+        cost: HashMap<Resource, u64>,
+    ) -> PlacementPreview {
+        return PlacementPreview {
+            group,
+            placement,
+            cost,
+        };
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return self.placement.is_ok();
+    }
+}
+
+// Everything the info panel reads about the tile under the cursor, gathered once per frame.
+pub struct InfoContext<'a> {
+    pub position: Position,
+    pub tile: &'a MapTile,
+    pub object: Option<&'a MapObject>,
+    pub slope: f64,
+    pub survey: Option<u64>,
+    pub preview: &'a PlacementPreview,
+    pub mode: &'a MapMode,
+    pub storage: &'a ResourceManager,
+    pub route: Option<Route>,
+    pub rovers: &'a RoverManager,
+    pub hazards: &'a HazardManager,
+}
+
+// What the map pane colours tiles by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapMode {
+    Terrain,
+    Height,
+    Temperature,
+    Atmosphere,
+    Pollution,
+    Deposits,
+    DepositAmount,
+    Power,
+    Deficit,
+    StorageFill,
+}
+
+impl MapMode {
+    pub fn next(&self) -> MapMode {
+        match self {
+            MapMode::Terrain => MapMode::Height,
+            MapMode::Height => MapMode::Temperature,
+            MapMode::Temperature => MapMode::Atmosphere,
+            MapMode::Atmosphere => MapMode::Pollution,
+            MapMode::Pollution => MapMode::Deposits,
+            MapMode::Deposits => MapMode::DepositAmount,
+            MapMode::DepositAmount => MapMode::Power,
+            MapMode::Power => MapMode::Deficit,
+            MapMode::Deficit => MapMode::StorageFill,
+            MapMode::StorageFill => MapMode::Terrain,
+        }
+    }
+}
+
+impl Display for MapMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let name = match self {
+            MapMode::Terrain => "Terrain",
+            MapMode::Height => "Height",
+            MapMode::Temperature => "Temperature",
+            MapMode::Atmosphere => "Atmosphere",
+            MapMode::Pollution => "Pollution",
+            MapMode::Deposits => "Deposits",
+            MapMode::DepositAmount => "Deposit Amount",
+            MapMode::Power => "Power",
+            MapMode::Deficit => "Deficit",
+            MapMode::StorageFill => "Storage Fill",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// The full screen pages of the interface, cycled with tab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    Game,
+    Statistics,
+    Bottlenecks,
+    Structures,
+}
+
+impl Screen {
+    pub fn next(&self) -> Screen {
+        match self {
+            Screen::Game => Screen::Statistics,
+            Screen::Statistics => Screen::Bottlenecks,
+            Screen::Bottlenecks => Screen::Structures,
+            Screen::Structures => Screen::Game,
+        }
+    }
+
+    pub fn previous(&self) -> Screen {
+        match self {
+            Screen::Game => Screen::Structures,
+            Screen::Statistics => Screen::Game,
+            Screen::Bottlenecks => Screen::Statistics,
+            Screen::Structures => Screen::Bottlenecks,
+        }
+    }
+}
+
 pub trait MenuSelector<T> {
     fn selected(&self) -> T;
     fn items(&self) -> Vec<ListItem>;
@@ -72,7 +212,7 @@ impl Menu {
 
 impl MenuSelector<StructureGroup> for Menu {
     fn selected(&self) -> StructureGroup {
-        return self.items[self.selected].clone();
+        return self.items[self.selected];
     }
 
     fn items(&self) -> Vec<ListItem> {
@@ -363,6 +503,308 @@ impl MenuSelector<Commodity> for FactoryCommoditySelect {
     }
 }
 
+pub struct TerraformProjectSelect {
+    selected: usize,
+    items: Vec<TerraformProject>,
+    selected_style: Style,
+    default_style: Style,
+}
+
+impl TerraformProjectSelect {
+    pub fn new(items: Vec<TerraformProject>) -> TerraformProjectSelect {
+        let selected = 0;
+
+        let selected_style = Style::default().bg(Color::Blue).fg(Color::White);
+        let default_style = Style::default().bg(Color::Gray).fg(Color::Black);
+
+        return TerraformProjectSelect {
+            selected,
+            items,
+            selected_style,
+            default_style,
+        };
+    }
+}
+
+impl MenuSelector<TerraformProject> for TerraformProjectSelect {
+    fn selected(&self) -> TerraformProject {
+        return self.items[self.selected];
+    }
+
+    fn items(&self) -> Vec<ListItem<'_>> {
+        let list = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, project)| {
+                let content = self.style(project.to_string(), index);
+                ListItem::new(content)
+            })
+            .collect();
+
+        return list;
+    }
+
+    fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        if self.selected == self.items.len() - 1 {
+            self.selected = 0;
+            return;
+        }
+
+        self.selected += 1;
+    }
+
+    fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        if self.selected == 0 {
+            self.selected = self.items.len() - 1;
+            return;
+        }
+
+        self.selected -= 1;
+    }
+
+    fn style(&self, name: String, index: usize) -> Span<'_> {
+        let style = if index == self.selected {
+            self.selected_style
+        } else {
+            self.default_style
+        };
+
+        return Span::styled(name, style);
+    }
+}
+
+pub struct MetricSelect {
+    selected: usize,
+    items: Vec<Metric>,
+    selected_style: Style,
+    default_style: Style,
+}
+
+impl MetricSelect {
+    pub fn new(items: Vec<Metric>) -> MetricSelect {
+        let selected = 0;
+
+        let selected_style = Style::default().bg(Color::Blue).fg(Color::White);
+        let default_style = Style::default().fg(Color::White);
+
+        return MetricSelect {
+            selected,
+            items,
+            selected_style,
+            default_style,
+        };
+    }
+}
+
+impl MenuSelector<Metric> for MetricSelect {
+    fn selected(&self) -> Metric {
+        return self.items[self.selected];
+    }
+
+    fn items(&self) -> Vec<ListItem<'_>> {
+        let list = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, metric)| {
+                let content = self.style(metric.to_string(), index);
+                ListItem::new(content)
+            })
+            .collect();
+
+        return list;
+    }
+
+    fn next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        if self.selected == self.items.len() - 1 {
+            self.selected = 0;
+            return;
+        }
+
+        self.selected += 1;
+    }
+
+    fn previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        if self.selected == 0 {
+            self.selected = self.items.len() - 1;
+            return;
+        }
+
+        self.selected -= 1;
+    }
+
+    fn style(&self, name: String, index: usize) -> Span<'_> {
+        let style = if index == self.selected {
+            self.selected_style
+        } else {
+            self.default_style
+        };
+
+        return Span::styled(name, style);
+    }
+}
+
+// Columns the structure list can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructureSort {
+    Position,
+    Group,
+    Status,
+    Energy,
+}
+
+impl StructureSort {
+    pub fn next(&self) -> StructureSort {
+        match self {
+            StructureSort::Position => StructureSort::Group,
+            StructureSort::Group => StructureSort::Status,
+            StructureSort::Status => StructureSort::Energy,
+            StructureSort::Energy => StructureSort::Position,
+        }
+    }
+}
+
+impl Display for StructureSort {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// A single line of the structure list.
+pub struct StructureRow {
+    pub position: Position,
+    pub group: StructureGroup,
+    pub name: String,
+    pub variant: String,
+    pub status: StructureStatus,
+    pub enabled: bool,
+    pub energy: u64,
+    pub output: String,
+}
+
+impl StructureRow {
+    pub fn new(position: &Position, structure: &Structure) -> StructureRow {
+        let blueprint = structure.blueprint();
+
+        return StructureRow {
+            position: position.clone(),
+            group: structure.group(),
+            name: structure.to_string(),
+            variant: format_structure_variant(structure),
+            status: blueprint.status(),
+            enabled: blueprint.is_enabled(),
+            energy: structure.energy_use(),
+            output: format_structure_output(structure),
+        };
+    }
+}
+
+// Selection, sorting and filtering of the structure list screen.
+pub struct StructureList {
+    selected: usize,
+    sort: StructureSort,
+    filter: Option<StructureGroup>,
+    groups: Vec<StructureGroup>,
+}
+
+impl StructureList {
+    pub fn new(groups: Vec<StructureGroup>) -> StructureList {
+        let selected = 0;
+        let sort = StructureSort::Position;
+        let filter = None;
+
+        return StructureList {
+            selected,
+            sort,
+            filter,
+            groups,
+        };
+    }
+
+    pub fn sort(&self) -> StructureSort {
+        return self.sort;
+    }
+
+    pub fn filter(&self) -> Option<StructureGroup> {
+        return self.filter;
+    }
+
+    pub fn next_sort(&mut self) {
+        self.sort = self.sort.next();
+    }
+
+    // Cycles through every structure group, then back to showing all of them.
+    pub fn next_filter(&mut self) {
+        let index = self
+            .filter
+            .and_then(|group| self.groups.iter().position(|g| *g == group));
+
+        self.filter = match index {
+            None => self.groups.first().copied(),
+            Some(index) => self.groups.get(index + 1).copied(),
+        };
+        self.selected = 0;
+    }
+
+    // The selected row, kept inside the list as it shrinks.
+    pub fn selected(&self, count: usize) -> usize {
+        return self.selected.min(count.saturating_sub(1));
+    }
+
+    pub fn next(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        self.selected = (self.selected(count) + 1) % count;
+    }
+
+    pub fn previous(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        self.selected = (self.selected(count) + count - 1) % count;
+    }
+
+    pub fn rows(&self, objects: &ObjectManager) -> Vec<StructureRow> {
+        let mut rows: Vec<StructureRow> = objects
+            .list()
+            .filter_map(|(position, o)| o.structure.as_ref().map(|s| (position, s)))
+            .filter(|(_, s)| self.filter.is_none_or(|group| s.group() == group))
+            .map(|(position, s)| StructureRow::new(position, s))
+            .collect();
+
+        rows.sort_by_key(|row| (row.position.y, row.position.x));
+
+        match self.sort {
+            StructureSort::Position => {}
+            StructureSort::Group => rows.sort_by_key(|row| row.name.clone()),
+            StructureSort::Status => rows.sort_by_key(|row| row.status.to_string()),
+            StructureSort::Energy => rows.sort_by_key(|row| std::cmp::Reverse(row.energy)),
+        }
+
+        return rows;
+    }
+}
+
 pub fn build_main_layout(area: Rect) -> Vec<Rect> {
     let layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -414,6 +856,32 @@ pub fn build_colony_layout(area: Rect) -> Vec<Rect> {
     return layout;
 }
 
+pub fn build_statistics_layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .margin(1)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .split(area);
+
+    return layout;
+}
+
+pub fn build_graph_layout(area: Rect) -> Vec<Rect> {
+    let layout = Layout::default()
+        .constraints(
+            [
+                Constraint::Percentage(55),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    return layout;
+}
+
 pub fn build_container_block(title: String) -> Block<'static> {
     let style = Style::default().fg(Color::White);
 
@@ -428,6 +896,8 @@ pub fn build_container_block(title: String) -> Block<'static> {
 pub fn draw_stats_widget_left(
     storage: &ResourceManager,
     energy: &EnergyManager,
+    habitable: f64,
+    pollution: &PollutionManager,
     elapsed: Duration,
     update_delta: u128,
     draw_delta: u128,
@@ -435,6 +905,13 @@ pub fn draw_stats_widget_left(
     // Time
     let mut items = vec![
         ListItem::new(format!("Time: {:.1} (seconds)", elapsed.as_secs_f32())),
+        ListItem::new(DayCycle::at(elapsed).to_string()),
+        ListItem::new(format!("Habitable: {:.1}%", habitable)),
+        ListItem::new(format!(
+            "Health: {:.0}% (air {})",
+            pollution.health(),
+            PollutionLevel::of(pollution.exposure())
+        )),
         ListItem::new(format!("Draw: {} (ms)", update_delta)),
         ListItem::new(format!("Update: {} (ms)", draw_delta)),
     ];
@@ -517,26 +994,312 @@ pub fn draw_stats_widget_right(storage: &ResourceManager) -> List<'static> {
     return list;
 }
 
-pub fn draw_console_widget(buffer: &String) -> Paragraph {
-    let block = build_container_block("Console".to_string());
+pub fn format_income(income: f64) -> String {
+    return format!("{:+.1}/min", income);
+}
 
-    let style = Style::default();
+pub fn draw_statistics_list_widget(
+    statistics: &StatisticsManager,
+    select: &MetricSelect,
+) -> List<'static> {
+    let items: Vec<ListItem> = statistics
+        .metrics()
+        .iter()
+        .enumerate()
+        .map(|(index, metric)| {
+            let content = format!(
+                "{:>14}: {:>9} {:>12}",
+                metric.to_string(),
+                statistics.latest(metric),
+                format_income(statistics.income_per_minute(metric))
+            );
+            let span = select.style(content, index);
+            ListItem::new(Span::styled(span.content.into_owned(), span.style))
+        })
+        .collect();
 
-    let mut log = String::new();
-    for l in buffer.lines().rev() {
-        log.push_str(format!("{}\n", l).as_str())
-    }
+    let block = build_container_block("Statistics".to_string());
 
-    let paragraph = Paragraph::new(log)
+    let list = List::new(items)
         .block(block)
-        .style(style)
-        .wrap(Wrap { trim: true });
+        .style(Style::default().fg(Color::White));
 
-    return paragraph;
+    return list;
 }
 
-pub fn draw_map_block() -> Block<'static> {
-    let block = build_container_block("Map".to_string()).border_type(BorderType::Thick);
+pub fn draw_statistics_chart_widget<'a>(
+    metric: &Metric,
+    points: &'a [(f64, f64)],
+    bounds: [f64; 2],
+    income: f64,
+) -> Chart<'a> {
+    let max = points.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+    let top = (max * 1.1).max(1.0);
+
+    let dataset = Dataset::default()
+        .name(metric.to_string())
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Cyan))
+        .data(points);
+
+    let title = format!("{} ({})", metric, format_income(income));
+    let block = build_container_block(title);
+
+    let x_axis = Axis::default()
+        .title("Time (s)")
+        .style(Style::default().fg(Color::Gray))
+        .bounds(bounds)
+        .labels(vec![
+            Span::raw(format!("{:.0}", bounds[0])),
+            Span::raw(format!("{:.0}", (bounds[0] + bounds[1]) / 2.0)),
+            Span::raw(format!("{:.0}", bounds[1])),
+        ]);
+
+    let y_axis = Axis::default()
+        .style(Style::default().fg(Color::Gray))
+        .bounds([0.0, top])
+        .labels(vec![
+            Span::raw("0"),
+            Span::raw(format!("{:.0}", top / 2.0)),
+            Span::raw(format!("{:.0}", top)),
+        ]);
+
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(x_axis)
+        .y_axis(y_axis);
+
+    return chart;
+}
+
+// Sparklines draw from the first sample, so only the most recent ones that fit are kept.
+pub fn draw_sparkline_widget<'a>(metric: &Metric, data: &'a [u64], width: u16) -> Sparkline<'a> {
+    let fit = (width as usize).saturating_sub(2);
+    let recent = &data[data.len().saturating_sub(fit)..];
+
+    let color = match metric {
+        Metric::EnergyDeficit => Color::Red,
+        Metric::EnergyStored => Color::Green,
+        _ => Color::Yellow,
+    };
+
+    let last = recent.last().copied().unwrap_or(0);
+    let block = build_container_block(format!("{}: {}", metric, last));
+
+    let sparkline = Sparkline::default()
+        .block(block)
+        .data(recent)
+        .style(Style::default().fg(color));
+
+    return sparkline;
+}
+
+// Summary line of a ranked bottleneck, shared by the report screen and the console.
+pub fn format_bottleneck(rank: usize, entry: &BottleneckEntry) -> String {
+    let count = entry.structures.len();
+    let noun = if count == 1 {
+        "structure"
+    } else {
+        "structures"
+    };
+
+    return format!(
+        "{}. {}: {} {} stalled for {} ticks, {}",
+        rank,
+        entry.bottleneck,
+        count,
+        noun,
+        entry.ticks,
+        entry.bottleneck.suggestion()
+    );
+}
+
+pub fn draw_bottleneck_widget(bottlenecks: &BottleneckManager) -> List<'static> {
+    let mut items = vec![ListItem::new(format!(
+        "Energy deficit: {} per tick, averaged over the last {} ticks",
+        bottlenecks.average_energy_deficit(),
+        bottlenecks.samples()
+    ))];
+
+    let report = bottlenecks.report();
+
+    if report.is_empty() {
+        items.push(ListItem::new("Every structure is running."));
+    }
+
+    for (index, entry) in report.iter().enumerate() {
+        let color = match entry.bottleneck {
+            Bottleneck::Energy => Color::Yellow,
+            Bottleneck::Resource(resource) => get_resource_color(&resource),
+            Bottleneck::Manufactured(_) => Color::LightCyan,
+            Bottleneck::Disconnected => Color::Gray,
+            Bottleneck::Damaged => Color::Red,
+            Bottleneck::OutputFull => Color::LightYellow,
+        };
+
+        items.push(ListItem::new(""));
+        items.push(ListItem::new(Span::styled(
+            format_bottleneck(index + 1, entry),
+            Style::default().fg(color),
+        )));
+
+        for (position, structure) in entry.structures.iter() {
+            items.push(ListItem::new(format!("    {} at {}", structure, position)));
+        }
+    }
+
+    let block = build_container_block("Bottlenecks".to_string());
+
+    let list = List::new(items)
+        .block(block)
+        .style(Style::default().fg(Color::White));
+
+    return list;
+}
+
+pub fn format_structure_variant(structure: &Structure) -> String {
+    match structure {
+        Structure::Mine { structure } => structure.resource().to_string(),
+        Structure::Refinery { structure } => structure.resources().join("/"),
+        Structure::Factory { structure } => structure.commodity().to_string(),
+        Structure::Terraformer { structure } => structure.project().to_string(),
+        _ => String::from("-"),
+    }
+}
+
+// What the structure makes on every tick it runs.
+pub fn format_structure_output(structure: &Structure) -> String {
+    let blueprint = structure.blueprint();
+
+    match structure {
+        Structure::Base { .. } | Structure::PowerPlant { .. } => {
+            format!("{} energy", blueprint.energy_out())
+        }
+        Structure::SolarPanel { .. } => format!("{} energy at noon", blueprint.energy_out()),
+        Structure::Mine { structure } => format!(
+            "{} {}, {} {}",
+            blueprint.resource_out(),
+            structure.resource(),
+            blueprint.manufactured_out(),
+            structure.manufactured()
+        ),
+        Structure::Refinery { structure } => format!(
+            "{} per {} ticks",
+            structure
+                .resources()
+                .map(|manufactured| format!("1 {}", manufactured))
+                .join(", "),
+            blueprint.cycle_ticks()
+        ),
+        Structure::Factory { structure } => format!(
+            "1 {} per {} ticks",
+            structure.commodity(),
+            blueprint.cycle_ticks()
+        ),
+        Structure::Storage { .. } => {
+            let fill = get_storage_fill(blueprint).unwrap_or(0.0);
+            format!("{:.0}% full", fill * 100.0)
+        }
+        Structure::Radar { .. } => format!("sight {}", blueprint.radius()),
+        Structure::Road { .. } => String::from("-"),
+        Structure::Shield { .. } => format!("cover {} tiles", SHIELD_RADIUS),
+        Structure::Terraformer { .. } => {
+            format!("1 tile per {} ticks", TERRAFORM_CYCLE_TICKS)
+        }
+        Structure::Scrubber { .. } => format!("clean {} tiles", SCRUBBER_RADIUS),
+    }
+}
+
+pub fn draw_structure_table_widget(rows: &[StructureRow], list: &StructureList) -> Table<'static> {
+    let header = Row::new(vec![
+        "Position",
+        "Structure",
+        "Variant",
+        "Status",
+        "Energy",
+        "Output",
+    ])
+    .style(Style::default().fg(Color::Yellow))
+    .bottom_margin(1);
+
+    let table_rows: Vec<Row> = rows
+        .iter()
+        .map(|row| {
+            let status_color = match row.status {
+                StructureStatus::Disabled | StructureStatus::Idle => Color::Gray,
+                status if status.has_deficit() => Color::Red,
+                _ => Color::Green,
+            };
+
+            Row::new(vec![
+                Cell::from(row.position.to_string()),
+                Cell::from(row.name.clone())
+                    .style(Style::default().fg(get_structure_group_color(&row.group))),
+                Cell::from(row.variant.clone()),
+                Cell::from(row.status.to_string()).style(Style::default().fg(status_color)),
+                Cell::from(row.energy.to_string()),
+                Cell::from(row.output.clone()),
+            ])
+        })
+        .collect();
+
+    let filter = match list.filter() {
+        Some(group) => group.to_string(),
+        None => String::from("All"),
+    };
+
+    let title = format!(
+        "Structures ({}) - Sort: {} (S) - Filter: {} (F) - Space toggles, T toggles all, Enter jumps",
+        rows.len(),
+        list.sort(),
+        filter
+    );
+    let block = build_container_block(title);
+
+    let table = Table::new(table_rows)
+        .header(header)
+        .block(block)
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().bg(Color::Blue))
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(24),
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Min(20),
+        ]);
+
+    return table;
+}
+
+pub fn draw_console_widget(buffer: &String) -> Paragraph {
+    let block = build_container_block("Console".to_string());
+
+    let style = Style::default();
+
+    let mut log = String::new();
+    for l in buffer.lines().rev() {
+        log.push_str(format!("{}\n", l).as_str())
+    }
+
+    let paragraph = Paragraph::new(log)
+        .block(block)
+        .style(style)
+        .wrap(Wrap { trim: true });
+
+    return paragraph;
+}
+
+pub fn draw_map_block(mode: &MapMode, overview: bool) -> Block<'static> {
+    let title = match mode {
+        _ if overview => "Map - Overview".to_string(),
+        MapMode::Terrain => "Map".to_string(),
+        _ => format!("Map - {}", mode),
+    };
+
+    let block = build_container_block(title).border_type(BorderType::Thick);
 
     return block;
 }
@@ -571,6 +1334,16 @@ pub fn draw_factory_select_widget(menu: &FactoryCommoditySelect) -> List {
     return list;
 }
 
+pub fn draw_terraform_select_widget(menu: &TerraformProjectSelect) -> List<'_> {
+    let block = build_container_block("Project Select".to_string());
+
+    let list = List::new(menu.items())
+        .block(block)
+        .style(Style::default().fg(Color::White));
+
+    return list;
+}
+
 pub fn draw_refinery_select_widget(menu: &RefineryResourceSelect) -> List {
     let block = build_container_block("Refinery Select".to_string());
 
@@ -581,6 +1354,22 @@ pub fn draw_refinery_select_widget(menu: &RefineryResourceSelect) -> List {
     return list;
 }
 
+pub fn format_deposit(deposit: &ResourceDeposit, survey: Option<u64>) -> String {
+    if let Some(progress) = survey {
+        return format!("Deposit: {} (surveying {}%)", deposit.resource, progress);
+    }
+
+    if !deposit.surveyed {
+        let (low, high) = deposit.estimate;
+        return format!("Deposit: {} (est. {}-{})", deposit.resource, low, high);
+    }
+
+    return format!(
+        "Deposit: {} ({}/{})",
+        deposit.resource, deposit.available, deposit.amount
+    );
+}
+
 pub fn format_mine_resource(resource_group: &Resource) -> String {
     return format!("Resource: {}", resource_group.to_string());
 }
@@ -609,6 +1398,226 @@ pub fn format_energy_io(blueprint: &StructureBlueprint) -> String {
     );
 }
 
+pub fn format_sight(blueprint: &StructureBlueprint) -> String {
+    let status = if blueprint.active() {
+        "scanning"
+    } else {
+        "offline"
+    };
+
+    return format!("Sight: {} tiles ({})", blueprint.radius(), status);
+}
+
+pub fn format_commodity_capacity(
+    blueprint: &StructureBlueprint,
+    commodity_group: &Commodity,
+) -> String {
+    let capacity = CommodityStorageTrait::capacity(blueprint, commodity_group);
+    let commodity = CommodityStorageTrait::commodity(blueprint, commodity_group);
+
+    return format!(
+        "{:<10} ({:>8} / {:<8})",
+        commodity_group.to_string(),
+        commodity,
+        capacity
+    );
+}
+
+// Width of the production cycle progress bar, in characters.
+const PROGRESS_BAR_WIDTH: u64 = 8;
+
+pub fn format_production(blueprint: &StructureBlueprint) -> ListItem<'static> {
+    let cycle = blueprint.cycle_ticks().max(1);
+    let progress = blueprint.progress();
+    let filled = (progress * PROGRESS_BAR_WIDTH / cycle) as usize;
+    let empty = PROGRESS_BAR_WIDTH as usize - filled;
+
+    let state = if blueprint.in_cycle() {
+        format!("{}/{} ticks", progress, cycle)
+    } else if blueprint.status() == StructureStatus::OutputFull {
+        String::from("waiting for room in the output")
+    } else {
+        String::from("waiting for inputs")
+    };
+
+    return ListItem::new(Spans::from(vec![
+        Span::raw("Cycle: "),
+        Span::styled("█".repeat(filled), Style::default().fg(Color::Green)),
+        Span::styled("░".repeat(empty), Style::default().fg(Color::DarkGray)),
+        Span::raw(format!(" {}", state)),
+    ]));
+}
+
+// Finished goods waiting at a producer for the next load to the base.
+pub fn format_output(blueprint: &StructureBlueprint) -> ListItem<'static> {
+    let finished = blueprint.finished();
+    let color = if finished >= OUTPUT_BUFFER_CAPACITY {
+        Color::Yellow
+    } else {
+        Color::Gray
+    };
+
+    return ListItem::new(Span::styled(
+        format!(
+            "Output: {}/{} waiting to ship",
+            finished, OUTPUT_BUFFER_CAPACITY
+        ),
+        Style::default().fg(color),
+    ));
+}
+
+pub fn format_retool(blueprint: &StructureBlueprint) -> ListItem<'static> {
+    if blueprint.downtime() > 0 {
+        return ListItem::new(Span::styled(
+            format!("Retooling: {} ticks left", blueprint.downtime()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
+    return ListItem::new("Press R to retool to the selected recipe");
+}
+
+// Hazards on their way to or raging over a position, and whether a shield covers it.
+pub fn format_hazards(position: &Position, hazards: &HazardManager) -> Vec<ListItem<'static>> {
+    let mut items: Vec<ListItem> = hazards
+        .hazards()
+        .iter()
+        .filter(|hazard| hazard.covers(position))
+        .map(|hazard| {
+            let text = if hazard.is_active() {
+                format!("{} overhead", hazard.kind)
+            } else {
+                format!(
+                    "{} expected in {} s",
+                    hazard.kind,
+                    hazard.warning * UPDATE_RATE_MS / 1000
+                )
+            };
+            ListItem::new(Span::styled(text, Style::default().fg(Color::LightRed)))
+        })
+        .collect();
+
+    if hazards.shielded(position) {
+        items.push(ListItem::new(Span::styled(
+            "Shielded from hazards",
+            Style::default().fg(Color::Cyan),
+        )));
+    }
+
+    return items;
+}
+
+pub fn format_rover(rover: &Rover, selected: bool) -> Vec<ListItem<'static>> {
+    let title = if selected {
+        format!("[ {} #{} ] selected", rover.kind(), rover.id())
+    } else {
+        format!("[ {} #{} ]", rover.kind(), rover.id())
+    };
+
+    let color = match rover.status() {
+        RoverStatus::Idle => Color::Gray,
+        RoverStatus::Moving => Color::Green,
+        RoverStatus::NoEnergy | RoverStatus::Blocked => Color::Red,
+    };
+
+    let mut items = vec![
+        ListItem::new(title),
+        ListItem::new(Span::styled(
+            format!("Status: {}", rover.status()),
+            Style::default().fg(color),
+        )),
+        ListItem::new(format!(
+            "Battery: {}/{}",
+            rover.battery(),
+            rover.kind().battery_capacity()
+        )),
+    ];
+
+    if let Some(destination) = rover.destination() {
+        items.push(ListItem::new(format!("Heading to {}", destination)));
+    }
+
+    if rover.kind() == RoverKind::Hauler {
+        items.push(ListItem::new("Hauls goods for structures it is parked by"));
+    }
+
+    return items;
+}
+
+pub fn format_route(route: Option<Route>) -> ListItem<'static> {
+    return match route {
+        Some(Route { tiles: 0, .. }) => ListItem::new("Route: part of the base"),
+        Some(route) => ListItem::new(format!(
+            "Route: {} tiles to the base, {} ticks on the way",
+            route.tiles, route.ticks
+        )),
+        None => ListItem::new(Span::styled(
+            "Route: not connected, build a Road or park a Hauler",
+            Style::default().fg(Color::Red),
+        )),
+    };
+}
+
+pub fn format_status(status: &StructureStatus) -> ListItem<'static> {
+    let color = match status {
+        StructureStatus::Running => Color::Green,
+        StructureStatus::Retooling | StructureStatus::OutputFull => Color::Yellow,
+        StructureStatus::Idle | StructureStatus::Disabled => Color::Gray,
+        _ => Color::Red,
+    };
+
+    return ListItem::new(Spans::from(vec![
+        Span::raw("Status: "),
+        Span::styled(status.to_string(), Style::default().fg(color)),
+    ]));
+}
+
+// A recipe with its inputs, coloured by whether the colony has enough of each right now.
+pub fn format_recipe(
+    output: String,
+    inputs: &HashMap<Resource, u64>,
+    energy: u64,
+    blueprint: &StructureBlueprint,
+    storage: &ResourceManager,
+) -> Vec<ListItem<'static>> {
+    let (state, color) = match blueprint.status() {
+        StructureStatus::Running => ("producing", Color::Green),
+        StructureStatus::Idle => ("idle", Color::Gray),
+        StructureStatus::Disabled => ("disabled", Color::Gray),
+        StructureStatus::Retooling => ("retooling", Color::Yellow),
+        StructureStatus::OutputFull => ("output full", Color::Yellow),
+        _ => ("starved", Color::Red),
+    };
+
+    let mut items = vec![ListItem::new(Spans::from(vec![
+        Span::raw(format!("{} <- {} energy ", output, energy)),
+        Span::styled(format!("[{}]", state), Style::default().fg(color)),
+    ]))];
+
+    for (resource, amount) in inputs.iter().sorted_by_key(|(r, _)| r.to_string()) {
+        let available = storage.resource(resource);
+        let buffered = blueprint.buffered(resource);
+        let color = if buffered + available >= *amount {
+            Color::Green
+        } else {
+            Color::Red
+        };
+
+        items.push(ListItem::new(Span::styled(
+            format!(
+                "  {:<10} {:>4} (buffer {}, have {})",
+                resource.to_string(),
+                amount,
+                buffered,
+                available
+            ),
+            Style::default().fg(color),
+        )));
+    }
+
+    return items;
+}
+
 pub fn format_battery(blueprint: &StructureBlueprint) -> String {
     let stored = BatteryTrait::stored(blueprint);
     let capacity = BatteryTrait::capacity(blueprint);
@@ -620,24 +1629,73 @@ pub fn format_battery(blueprint: &StructureBlueprint) -> String {
     );
 }
 
-pub fn draw_info_widget(
-    position: Position,
-    tile: &MapTile,
-    object: Option<&MapObject>,
-) -> List<'static> {
+pub fn format_placement(preview: &PlacementPreview) -> Vec<ListItem<'static>> {
+    let status = match &preview.placement {
+        Ok(_) => Span::styled("Placement: allowed", Style::default().fg(Color::Green)),
+        Err(error) => Span::styled(error.to_string(), Style::default().fg(Color::Red)),
+    };
+
+    return vec![
+        ListItem::new(format!(
+            "[ Build {} {} ]",
+            preview.group,
+            preview.group.footprint()
+        )),
+        ListItem::new(format!("Cost: {}", format_resources(&preview.cost))),
+        ListItem::new(status),
+    ];
+}
+
+pub fn draw_info_widget(context: InfoContext) -> List<'static> {
+    let InfoContext {
+        position,
+        tile,
+        object,
+        slope,
+        survey,
+        preview,
+        mode,
+        storage,
+        route,
+        rovers,
+        hazards,
+    } = context;
+
     let block = build_container_block("Info".to_string());
 
-    let mut items = vec![
-        ListItem::new(format!("Position: ({}, {})", position.x, position.y)),
-        ListItem::new(format!("Flora: {}", tile.flora.to_string())),
-    ];
+    let mut items = vec![ListItem::new(format!(
+        "Position: ({}, {})",
+        position.x, position.y
+    ))];
 
-    if tile.is_resource {
-        let deposit = object.unwrap().deposit.unwrap();
-        items.push(ListItem::new(format!(
-            "Deposit: {} ({}/{})",
-            deposit.resource, deposit.available, deposit.amount
-        )));
+    if !tile.explored {
+        items.push(ListItem::new("Unexplored"));
+        items.extend(format_placement(preview));
+        items.extend(format_legend(mode));
+
+        return List::new(items)
+            .block(block)
+            .style(Style::default().fg(Color::White));
+    }
+
+    items.push(ListItem::new(format!("Flora: {}", tile.flora.to_string())));
+    items.push(ListItem::new(format!(
+        "Elevation: {} m (slope {:.2})",
+        tile.elevation(),
+        slope
+    )));
+
+    items.extend(format_climate(tile));
+
+    if let Some(deposit) = object.and_then(|o| o.deposit.as_ref()) {
+        items.push(ListItem::new(format_deposit(deposit, survey)));
+    }
+
+    items.extend(format_hazards(&position, hazards));
+
+    if let Some(rover) = rovers.at(&position) {
+        let selected = rovers.selected().map(|r| r.id()) == Some(rover.id());
+        items.extend(format_rover(rover, selected));
     }
 
     if object.is_some() {
@@ -647,6 +1705,28 @@ pub fn draw_info_widget(
             let structure = structure.unwrap();
             let structure_content = format!("[ {} ]", structure.to_string());
             items.push(ListItem::new(structure_content));
+            items.push(format_status(&structure.blueprint().status()));
+
+            if structure.blueprint().is_damaged() {
+                items.push(ListItem::new(Span::styled(
+                    format!("Repairs: {} ticks left", structure.blueprint().repair()),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+
+            if structure.emission() > 0.0 {
+                items.push(ListItem::new(format!(
+                    "Pollution: {:.1} per tick while running",
+                    structure.emission()
+                )));
+            }
+
+            if structure.blueprint().climate_load() > 0 {
+                items.push(ListItem::new(format!(
+                    "Climate control: +{}% energy",
+                    structure.blueprint().climate_load()
+                )));
+            }
 
             match structure {
                 Structure::Base { ref structure } => {
@@ -660,12 +1740,67 @@ pub fn draw_info_widget(
                         )));
                     }
                 }
-                Structure::PowerPlant { .. } => {}
+                Structure::PowerPlant { ref structure } => {
+                    items.push(ListItem::new(format_energy_io(structure.blueprint())));
+                }
+                Structure::SolarPanel { ref structure } => {
+                    items.push(ListItem::new(format_energy_io(structure.blueprint())));
+                    items.push(ListItem::new("Output follows the sun"));
+                }
                 Structure::Mine { ref structure } => {
                     items.push(ListItem::new(format_mine_resource(structure.resource())));
                 }
-                Structure::Refinery { .. } => {}
-                Structure::Factory { .. } => {}
+                Structure::Refinery { ref structure } => {
+                    let component = structure
+                        .blueprint()
+                        .get_component(&ComponentName::RefineryOutputComponent);
+
+                    if let Ok(ComponentGroup::RefineryOutput { component }) = component {
+                        items.push(ListItem::new(format!(
+                            "Energy draw: {}",
+                            component.resource_required_sum()
+                        )));
+                        items.push(format_retool(structure.blueprint()));
+                        items.push(format_production(structure.blueprint()));
+                        items.push(format_output(structure.blueprint()));
+
+                        for manufactured in structure.resources() {
+                            items.extend(format_recipe(
+                                format!(
+                                    "{} {}",
+                                    component.manufactured_out[manufactured], manufactured
+                                ),
+                                &component.resource_required[manufactured],
+                                component.energy_required[manufactured],
+                                structure.blueprint(),
+                                storage,
+                            ));
+                        }
+                    }
+                }
+                Structure::Factory { ref structure } => {
+                    let component = structure
+                        .blueprint()
+                        .get_component(&ComponentName::FactoryOutputComponent);
+
+                    if let Ok(ComponentGroup::FactoryOutput { component }) = component {
+                        items.push(ListItem::new(format!(
+                            "Energy draw: {}",
+                            component.energy_required
+                        )));
+                        items.push(format_retool(structure.blueprint()));
+                        items.push(format_production(structure.blueprint()));
+                        items.push(format_output(structure.blueprint()));
+
+                        items.extend(format_recipe(
+                            format!("{} {}", component.commodity_out, structure.commodity()),
+                            &component.resource_required,
+                            component.energy_required,
+                            structure.blueprint(),
+                            storage,
+                        ));
+                    }
+                }
                 Structure::Storage { ref structure } => {
                     for resource in structure.blueprint().resources() {
                         items.push(ListItem::new(format_resource_capacity(
@@ -673,11 +1808,53 @@ pub fn draw_info_widget(
                             resource,
                         )));
                     }
+
+                    for commodity in structure.blueprint().commodities() {
+                        items.push(ListItem::new(format_commodity_capacity(
+                            structure.blueprint(),
+                            commodity,
+                        )));
+                    }
+                }
+                Structure::Radar { ref structure } => {
+                    items.push(ListItem::new(format_energy_io(structure.blueprint())));
+                    items.push(ListItem::new(format_sight(structure.blueprint())));
                 }
+                Structure::Road { .. } => {}
+                Structure::Shield { ref structure } => {
+                    items.push(ListItem::new(format!(
+                        "Energy draw: {}",
+                        structure.blueprint().energy_in()
+                    )));
+                    items.push(ListItem::new(format!(
+                        "Shields {} tiles from hazards",
+                        SHIELD_RADIUS
+                    )));
+                }
+                Structure::Terraformer { ref structure } => {
+                    items.extend(format_terraformer(structure, storage));
+                }
+                Structure::Scrubber { ref structure } => {
+                    items.push(ListItem::new(format!(
+                        "Energy draw: {}",
+                        structure.blueprint().energy_in()
+                    )));
+                    items.push(ListItem::new(format!(
+                        "Cleans the air within {} tiles",
+                        SCRUBBER_RADIUS
+                    )));
+                }
+            }
+
+            if structure.trades_goods() {
+                items.push(format_route(route));
             }
         }
     }
 
+    items.extend(format_placement(preview));
+    items.extend(format_legend(mode));
+
     let list = List::new(items)
         .block(block)
         .style(Style::default().fg(Color::White));
@@ -685,37 +1862,376 @@ pub fn draw_info_widget(
     return list;
 }
 
-fn get_flora_style(flora: &Flora) -> Style {
+fn get_flora_color(flora: &Flora) -> Color {
     match flora {
-        Flora::Water => Style::default().bg(Color::Rgb(32, 178, 170)),
-        Flora::Sand => Style::default().bg(Color::Yellow),
-        Flora::Dirt => Style::default().bg(Color::Rgb(139, 69, 19)),
-        Flora::Grass => Style::default().bg(Color::Rgb(0, 128, 0)),
-        Flora::Rock => Style::default().bg(Color::Rgb(0, 0, 0)),
+        Flora::Water => Color::Rgb(32, 178, 170),
+        Flora::Sand => Color::Rgb(230, 200, 100),
+        Flora::Dirt => Color::Rgb(139, 69, 19),
+        Flora::Grass => Color::Rgb(0, 128, 0),
+        Flora::Rock => Color::Rgb(0, 0, 0),
+        Flora::Ice => Color::Rgb(200, 230, 255),
+        Flora::Lava => Color::Rgb(207, 16, 32),
+        Flora::Crater => Color::Rgb(90, 90, 90),
+        Flora::SaltFlat => Color::Rgb(240, 240, 230),
+        Flora::ToxicSwamp => Color::Rgb(110, 140, 20),
+    }
+}
+
+// Terrain darkens at night, `light` goes from 0.0 (black) to 1.0 (full daylight).
+fn get_flora_style(flora: &Flora, light: f64) -> Style {
+    let color = match get_flora_color(flora) {
+        Color::Rgb(r, g, b) => Color::Rgb(
+            (r as f64 * light) as u8,
+            (g as f64 * light) as u8,
+            (b as f64 * light) as u8,
+        ),
+        color => color,
+    };
+
+    Style::default().bg(color)
+}
+
+fn get_structure_group_color(group: &StructureGroup) -> Color {
+    match group {
+        StructureGroup::Base => Color::White,
+        StructureGroup::Power => Color::LightYellow,
+        StructureGroup::Solar => Color::Yellow,
+        StructureGroup::Mine => Color::LightMagenta,
+        StructureGroup::Refinery => Color::LightCyan,
+        StructureGroup::Factory => Color::LightBlue,
+        StructureGroup::Storage => Color::Gray,
+        StructureGroup::Radar => Color::LightGreen,
+        StructureGroup::Road => Color::DarkGray,
+        StructureGroup::Shield => Color::Cyan,
+        StructureGroup::Terraformer => Color::LightGreen,
+        StructureGroup::Scrubber => Color::Green,
+    }
+}
+
+// Grayscale from low (dark) to high (bright) ground.
+fn get_height_style(height: f64) -> Style {
+    let level = ((height.clamp(-1.0, 1.0) + 1.0) / 2.0 * 255.0).round() as u8;
+    Style::default().bg(Color::Rgb(level, level, level))
+}
+
+fn format_terraformer(
+    terraformer: &Terraformer,
+    storage: &ResourceManager,
+) -> Vec<ListItem<'static>> {
+    let project = terraformer.project();
+
+    let mut items = vec![
+        ListItem::new(format!(
+            "Energy draw: {}",
+            terraformer.blueprint().energy_in()
+        )),
+        ListItem::new(format!("Project: {}", project)),
+        format_retool(terraformer.blueprint()),
+        format_production(terraformer.blueprint()),
+    ];
+
+    let blueprint = terraformer.blueprint();
+
+    let mut inputs: Vec<(String, u64, u64, u64)> = project
+        .resources()
+        .iter()
+        .map(|(resource, amount)| {
+            (
+                resource.to_string(),
+                *amount,
+                blueprint.buffered(resource),
+                storage.resource(resource),
+            )
+        })
+        .collect();
+
+    for (manufactured, amount) in project.manufactured().iter() {
+        let stored = storage
+            .manufactured()
+            .find(|(m, _)| *m == manufactured)
+            .map_or(0, |(_, stored)| *stored);
+        let buffered = blueprint.buffered_manufactured(manufactured);
+        inputs.push((manufactured.to_string(), *amount, buffered, stored));
+    }
+
+    for (name, amount, buffered, stored) in inputs {
+        let color = if buffered + stored >= amount {
+            Color::Green
+        } else {
+            Color::Red
+        };
+
+        items.push(ListItem::new(Span::styled(
+            format!(
+                "  {:<10} {:>4} (buffer {}, have {})",
+                name, amount, buffered, stored
+            ),
+            Style::default().fg(color),
+        )));
+    }
+
+    match terraformer.target() {
+        Some(target) => items.push(ListItem::new(format!("Next tile: {}", target))),
+        None => items.push(ListItem::new(format!(
+            "Nothing left to change within {} tiles",
+            TERRAFORM_RADIUS
+        ))),
+    }
+
+    return items;
+}
+
+fn format_climate(tile: &MapTile) -> Vec<ListItem<'static>> {
+    let habitability = match tile.hostility() {
+        Some(reason) => format!("Hostile: {}", reason),
+        None => "Habitable".to_string(),
+    };
+
+    return vec![
+        ListItem::new(format!(
+            "Climate: {:.0} °C, {:.0} kPa, O2 {:.0}%",
+            tile.temperature, tile.pressure, tile.oxygen
+        )),
+        ListItem::new(habitability),
+        ListItem::new(format!(
+            "Pollution: {:.1} ({})",
+            tile.pollution,
+            PollutionLevel::of(tile.pollution)
+        )),
+    ];
+}
+
+// Blue for the coldest ground through to red for the hottest.
+fn get_temperature_color(temperature: f64) -> Color {
+    let ratio = ((temperature - COLDEST) / (HOTTEST - COLDEST)).clamp(0.0, 1.0);
+    return Color::Rgb((ratio * 255.0) as u8, 60, ((1.0 - ratio) * 255.0) as u8);
+}
+
+// Temperatures at the ends of the temperature overlay scale.
+const COLDEST: f64 = -60.0;
+const HOTTEST: f64 = 80.0;
+
+// Breathable air is green, otherwise brighter the denser the air.
+fn get_atmosphere_color(tile: &MapTile) -> Color {
+    let (low, high) = BREATHABLE_PRESSURE;
+
+    if tile.pressure >= low && tile.pressure <= high && tile.oxygen >= BREATHABLE_OXYGEN {
+        return Color::Rgb(40, 200, 80);
+    }
+
+    let level = (tile.pressure / (high + low) * 200.0).clamp(0.0, 200.0) as u8 + 20;
+    return Color::Rgb(level / 2, level / 2, level);
+}
+
+// Green for clean air, turning red by the time grass dies off.
+fn get_pollution_color(pollution: f64) -> Color {
+    return get_gradient_color(1.0 - pollution / GRASS_DEGRADE_POLLUTION);
+}
+
+// Tiles an overlay has nothing to say about are drawn dark.
+const OVERLAY_DIM: Color = Color::Rgb(40, 40, 40);
+
+fn get_resource_color(resource: &Resource) -> Color {
+    match resource {
+        Resource::Iron => Color::Rgb(183, 65, 14),
+        Resource::Aluminum => Color::Rgb(190, 190, 200),
+        Resource::Carbon => Color::Rgb(60, 60, 60),
+        Resource::Silica => Color::Rgb(230, 220, 170),
+        Resource::Uranium => Color::Rgb(80, 220, 60),
+        Resource::Water => Color::Rgb(30, 110, 230),
+    }
+}
+
+// From red at zero, through yellow, to green at one.
+fn get_gradient_color(ratio: f64) -> Color {
+    let ratio = ratio.clamp(0.0, 1.0);
+
+    if ratio < 0.5 {
+        return Color::Rgb(220, (ratio * 2.0 * 220.0) as u8, 0);
+    }
+
+    return Color::Rgb(((1.0 - ratio) * 2.0 * 220.0) as u8, 220, 0);
+}
+
+// How full the resource storage of a structure is, if it has one.
+fn get_storage_fill(blueprint: &StructureBlueprint) -> Option<f64> {
+    if !blueprint.has_component(&ComponentName::ResourceStorageComponent) {
+        return None;
+    }
+
+    let resources = ResourceStorageTrait::resources(blueprint);
+    let stored: u64 = resources
+        .iter()
+        .map(|r| ResourceStorageTrait::resource(blueprint, r))
+        .sum();
+    let capacity: u64 = resources
+        .iter()
+        .map(|r| ResourceStorageTrait::capacity(blueprint, r))
+        .sum();
+
+    return Some(stored as f64 / capacity.max(1) as f64);
+}
+
+fn get_overlay_color(object: Option<&MapObject>, mode: &MapMode) -> Option<Color> {
+    let structure = object.and_then(|o| o.structure.as_ref());
+    let deposit = object.and_then(|o| o.deposit.as_ref());
+
+    match mode {
+        MapMode::Deposits => deposit.map(|d| get_resource_color(&d.resource)),
+        MapMode::DepositAmount => deposit.map(|d| match d.surveyed {
+            true => get_gradient_color(d.available as f64 / d.amount.max(1) as f64),
+            false => Color::Gray,
+        }),
+        MapMode::Power => structure.map(|s| match s.blueprint().status() {
+            StructureStatus::Disabled => Color::Gray,
+            status if status.is_powered() => Color::Green,
+            _ => Color::Red,
+        }),
+        MapMode::Deficit => structure.map(|s| match s.blueprint().status() {
+            status if status.has_deficit() => Color::Red,
+            StructureStatus::Idle | StructureStatus::Disabled => Color::Gray,
+            _ => Color::Green,
+        }),
+        MapMode::StorageFill => structure
+            .and_then(|s| get_storage_fill(s.blueprint()))
+            .map(|fill| get_gradient_color(1.0 - fill)),
+        _ => None,
+    }
+}
+
+fn get_tile_style(tile: &MapTile, object: Option<&MapObject>, mode: &MapMode, light: f64) -> Style {
+    match mode {
+        MapMode::Terrain => get_flora_style(&tile.flora, light),
+        MapMode::Height => get_height_style(tile.height),
+        MapMode::Temperature => Style::default().bg(get_temperature_color(tile.temperature)),
+        MapMode::Atmosphere => Style::default().bg(get_atmosphere_color(tile)),
+        MapMode::Pollution => Style::default().bg(get_pollution_color(tile.pollution)),
+        _ => Style::default().bg(get_overlay_color(object, mode).unwrap_or(OVERLAY_DIM)),
     }
 }
 
+fn legend_item(color: Color, label: &str) -> ListItem<'static> {
+    return ListItem::new(Spans::from(vec![
+        Span::styled("  ", Style::default().bg(color)),
+        Span::raw(format!(" {}", label)),
+    ]));
+}
+
+pub fn format_legend(mode: &MapMode) -> Vec<ListItem<'static>> {
+    let mut items = match mode {
+        MapMode::Terrain => return vec![],
+        MapMode::Height => vec![
+            legend_item(Color::Rgb(0, 0, 0), "Low ground"),
+            legend_item(Color::Rgb(128, 128, 128), "Datum"),
+            legend_item(Color::Rgb(255, 255, 255), "High ground"),
+        ],
+        MapMode::Temperature => vec![
+            legend_item(get_temperature_color(COLDEST), &format!("{} °C", COLDEST)),
+            legend_item(get_temperature_color(10.0), "10 °C"),
+            legend_item(get_temperature_color(HOTTEST), &format!("{} °C", HOTTEST)),
+        ],
+        MapMode::Atmosphere => vec![
+            legend_item(Color::Rgb(40, 200, 80), "Breathable"),
+            legend_item(Color::Rgb(10, 10, 20), "Vacuum"),
+            legend_item(Color::Rgb(110, 110, 220), "Dense air"),
+        ],
+        MapMode::Pollution => vec![
+            legend_item(get_pollution_color(0.0), "Clean"),
+            legend_item(get_pollution_color(HEALTHY_POLLUTION), "Unhealthy"),
+            legend_item(get_pollution_color(GRASS_DEGRADE_POLLUTION), "Kills grass"),
+        ],
+        MapMode::Deposits => {
+            let resources = [
+                Resource::Iron,
+                Resource::Aluminum,
+                Resource::Carbon,
+                Resource::Silica,
+                Resource::Uranium,
+                Resource::Water,
+            ];
+
+            resources
+                .iter()
+                .map(|r| legend_item(get_resource_color(r), &r.to_string()))
+                .collect()
+        }
+        MapMode::DepositAmount => vec![
+            legend_item(get_gradient_color(1.0), "Untouched"),
+            legend_item(get_gradient_color(0.5), "Half left"),
+            legend_item(get_gradient_color(0.0), "Depleted"),
+            legend_item(Color::Gray, "Unsurveyed"),
+        ],
+        MapMode::Power => vec![
+            legend_item(Color::Green, "Powered"),
+            legend_item(Color::Red, "Unpowered"),
+            legend_item(Color::Gray, "Disabled"),
+        ],
+        MapMode::Deficit => vec![
+            legend_item(Color::Green, "Running"),
+            legend_item(Color::Red, "Deficit this tick"),
+            legend_item(Color::Gray, "Idle or disabled"),
+        ],
+        MapMode::StorageFill => vec![
+            legend_item(get_gradient_color(1.0), "Empty"),
+            legend_item(get_gradient_color(0.5), "Half full"),
+            legend_item(get_gradient_color(0.0), "Full"),
+        ],
+    };
+
+    items.insert(0, ListItem::new(format!("[ Legend: {} ]", mode)));
+    return items;
+}
+
 fn get_structure_symbol(structure: &Structure) -> char {
-    match structure {
-        Structure::Base { .. } => 'B',
-        Structure::PowerPlant { .. } => 'P',
-        Structure::Mine { .. } => 'M',
-        Structure::Factory { .. } => 'F',
-        Structure::Refinery { .. } => 'R',
-        Structure::Storage { .. } => 'S',
+    return get_structure_group_symbol(&structure.group());
+}
+
+fn get_structure_group_symbol(group: &StructureGroup) -> char {
+    match group {
+        StructureGroup::Base => 'B',
+        StructureGroup::Power => 'P',
+        StructureGroup::Solar => '*',
+        StructureGroup::Mine => 'M',
+        StructureGroup::Factory => 'F',
+        StructureGroup::Refinery => 'R',
+        StructureGroup::Storage => 'S',
+        StructureGroup::Radar => 'D',
+        StructureGroup::Road => '#',
+        StructureGroup::Shield => 'O',
+        StructureGroup::Terraformer => 'T',
+        StructureGroup::Scrubber => 'X',
+    }
+}
+
+fn get_preview_style(style: Style, preview: &PlacementPreview) -> Style {
+    if preview.is_valid() {
+        return style.fg(Color::LightGreen);
     }
+
+    return style.fg(Color::LightRed);
 }
 
 pub fn render_map(
     map: &GameMap,
     objects: &ObjectManager,
     position: Position,
+    preview: &PlacementPreview,
+    mode: &MapMode,
+    rovers: &RoverManager,
+    light: f64,
 ) -> Vec<Spans<'static>> {
-    let y = position.y as usize;
-    let x = position.x as usize;
-
     let map_render = map.cache();
 
+    let selected_rover = rovers.selected().map(|r| r.id());
+
+    // Tiles covered by the structure under the cursor, or by the ghost of the one to build.
+    let selected_owner = objects.owner(&position).cloned();
+    let ghost: HashSet<Position> = if selected_owner.is_none() {
+        HashSet::from_iter(preview.group.footprint().cells(&position))
+    } else {
+        HashSet::new()
+    };
+
     let text = map_render
         .iter()
         .enumerate()
@@ -724,47 +2240,60 @@ pub fn render_map(
                 .iter()
                 .enumerate()
                 .map(|(j, tile)| {
-                    let selected = y == i && x == j;
+                    let position = Position::new(j as i16, i as i16);
 
-                    let mut style = get_flora_style(&tile.flora);
+                    // Unexplored ground shows nothing, except the cursor and the ghost.
+                    if !tile.explored {
+                        let style = Style::default().bg(Color::Rgb(20, 20, 20));
 
-                    let block_symbol = if selected {
-                        BlockType::Selected
-                    } else {
-                        BlockType::Light
-                    };
+                        if ghost.contains(&position) {
+                            let symbol = get_structure_group_symbol(&preview.group);
+                            let style = get_preview_style(style, preview);
+                            return Span::styled(symbol.to_string(), style);
+                        }
 
-                    let position = Position::new(j as i16, i as i16);
-                    let object = objects.get(&position);
-
-                    if object.is_some() {
-                        if object.unwrap().structure.is_some() {
-                            let structure = object.unwrap().structure.as_ref();
-                            if structure.is_some() {
-                                let structure_symbol = get_structure_symbol(structure.unwrap());
-
-                                if selected {
-                                    style = style.fg(Color::Red);
-                                }
-
-                                return Span::styled(
-                                    char::from(structure_symbol).to_string(),
-                                    style,
-                                );
-                            }
-                        } else if object.unwrap().deposit.is_some() {
-                            if selected {
-                                style = style.fg(Color::Red);
-                            }
-
-                            return Span::styled(
-                                char::from(BlockType::Resource).to_string(),
-                                style,
-                            );
+                        return Span::styled(" ".to_string(), style);
+                    }
+
+                    let object = objects.resolve(&position);
+
+                    let mut style = get_tile_style(tile, object, mode, light);
+
+                    // Rovers drive over the terrain and roads, so they are drawn on top.
+                    if let Some(rover) = rovers.at(&position) {
+                        let color = if selected_rover == Some(rover.id()) {
+                            Color::Red
+                        } else {
+                            Color::White
+                        };
+
+                        let symbol = rover.kind().symbol().to_string();
+                        return Span::styled(symbol, style.fg(color));
+                    }
+
+                    if let Some(structure) = object.and_then(|o| o.structure.as_ref()) {
+                        let structure_symbol = get_structure_symbol(structure);
+
+                        let owner = objects.owner(&position);
+                        if owner.is_some() && owner == selected_owner.as_ref() {
+                            style = style.fg(Color::Red);
                         }
+
+                        return Span::styled(char::from(structure_symbol).to_string(), style);
+                    }
+
+                    // Ghost of the structure selected in the build menu.
+                    if ghost.contains(&position) {
+                        let symbol = get_structure_group_symbol(&preview.group);
+                        let style = get_preview_style(style, preview);
+                        return Span::styled(symbol.to_string(), style);
                     }
 
-                    return Span::styled(char::from(block_symbol).to_string(), style);
+                    if object.and_then(|o| o.deposit.as_ref()).is_some() {
+                        return Span::styled(char::from(BlockType::Resource).to_string(), style);
+                    }
+
+                    return Span::styled(char::from(BlockType::Light).to_string(), style);
                 })
                 .collect();
             return Spans::from(spans);
@@ -774,6 +2303,86 @@ pub fn render_map(
     return text;
 }
 
+// How many tiles one overview character covers horizontally, vertically it covers twice that.
+pub fn overview_scale(map: &GameMap, area: Rect) -> u16 {
+    let width = area.width.max(1);
+    let height = area.height.max(1) * 2;
+
+    let scale_x = map.width().div_ceil(width);
+    let scale_y = map.height().div_ceil(height);
+
+    return scale_x.max(scale_y).max(1);
+}
+
+fn get_overview_color(
+    map: &GameMap,
+    objects: &ObjectManager,
+    position: &Position,
+    light: f64,
+) -> Color {
+    let tile = &map.cache()[position.y as usize][position.x as usize];
+
+    if !tile.explored {
+        return Color::Rgb(20, 20, 20);
+    }
+
+    if let Some(structure) = objects.resolve(position).and_then(|o| o.structure.as_ref()) {
+        return get_structure_group_color(&structure.group());
+    }
+
+    let style = get_flora_style(&tile.flora, light);
+
+    return style.bg.unwrap_or(Color::Rgb(0, 0, 0));
+}
+
+// The whole map shrunk into half-block characters, the upper tile as foreground
+// and the lower one as background, with the cursor marked in red.
+pub fn render_overview(
+    map: &GameMap,
+    objects: &ObjectManager,
+    position: Position,
+    scale: u16,
+    light: f64,
+) -> Vec<Spans<'static>> {
+    let step = scale as i16;
+    let mut text = Vec::new();
+
+    let covers = |cell: &Position, row: i16, column: i16| {
+        cell.x >= column && cell.x < column + step && cell.y >= row && cell.y < row + step
+    };
+
+    for row in (0..map.height() as i16).step_by(2 * scale as usize) {
+        let mut spans = Vec::new();
+
+        for column in (0..map.width() as i16).step_by(scale as usize) {
+            let upper = Position::new(column, row);
+            let lower = Position::new(column, row + step);
+
+            let mut fg = get_overview_color(map, objects, &upper, light);
+            let mut bg = if map.contains(&lower) {
+                get_overview_color(map, objects, &lower, light)
+            } else {
+                Color::Rgb(0, 0, 0)
+            };
+
+            if covers(&position, row, column) {
+                fg = Color::Red;
+            } else if covers(&position, row + step, column) {
+                bg = Color::Red;
+            }
+
+            spans.push(Span::styled(
+                "▀".to_string(),
+                Style::default().fg(fg).bg(bg),
+            ));
+        }
+
+        text.push(Spans::from(spans));
+    }
+
+    return text;
+}
+
 pub fn draw_map_widget(text: &Vec<Spans<'static>>) -> Paragraph<'static> {
     let p = Paragraph::new(text.clone())
         .block(Block::default().borders(Borders::NONE))
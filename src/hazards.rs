@@ -0,0 +1,122 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::game::Position;
+
+// Share of its output a power plant keeps inside a dust storm, in percent.
+pub const DUST_STORM_OUTPUT: u64 = 40;
+
+// Share of the stored energy a solar flare drains from a base, in percent.
+pub const SOLAR_FLARE_DRAIN: u64 = 50;
+
+// Update ticks of repairs after a meteor strike or an equipment failure.
+pub const METEOR_REPAIR_TICKS: u64 = 80;
+pub const FAILURE_REPAIR_TICKS: u64 = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HazardKind {
+    DustStorm,
+    MeteorStrike,
+    SolarFlare,
+    EquipmentFailure,
+}
+
+impl HazardKind {
+    pub fn all() -> Vec<HazardKind> {
+        return vec![
+            HazardKind::DustStorm,
+            HazardKind::MeteorStrike,
+            HazardKind::SolarFlare,
+            HazardKind::EquipmentFailure,
+        ];
+    }
+
+    // Average update ticks between two events of this kind.
+    pub fn interval(&self) -> u64 {
+        match self {
+            HazardKind::DustStorm => 1500,
+            HazardKind::MeteorStrike => 1200,
+            HazardKind::SolarFlare => 2000,
+            HazardKind::EquipmentFailure => 900,
+        }
+    }
+
+    // Update ticks between the announcement and the hit, radars double it.
+    pub fn warning(&self) -> u64 {
+        match self {
+            HazardKind::DustStorm => 50,
+            HazardKind::MeteorStrike => 25,
+            HazardKind::SolarFlare => 40,
+            HazardKind::EquipmentFailure => 15,
+        }
+    }
+
+    // Update ticks the hazard lasts once it hits.
+    pub fn duration(&self) -> u64 {
+        match self {
+            HazardKind::DustStorm => 125,
+            _ => 1,
+        }
+    }
+
+    // Tiles around the target the hazard reaches, solar flares hit the whole colony.
+    pub fn radius(&self) -> f64 {
+        match self {
+            HazardKind::DustStorm => 8.0,
+            HazardKind::MeteorStrike => 1.0,
+            HazardKind::SolarFlare => f64::MAX,
+            HazardKind::EquipmentFailure => 0.0,
+        }
+    }
+
+    // What the player can do about it, shown with the warning.
+    pub fn mitigation(&self) -> &'static str {
+        match self {
+            HazardKind::DustStorm => "power plants under a Shield keep their output",
+            HazardKind::MeteorStrike => "a Shield deflects it",
+            HazardKind::SolarFlare => "bases under a Shield keep their charge",
+            HazardKind::EquipmentFailure => "a Construction Rover nearby repairs twice as fast",
+        }
+    }
+}
+
+impl Display for HazardKind {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            HazardKind::DustStorm => write!(f, "Dust storm"),
+            HazardKind::MeteorStrike => write!(f, "Meteor strike"),
+            HazardKind::SolarFlare => write!(f, "Solar flare"),
+            HazardKind::EquipmentFailure => write!(f, "Equipment failure"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub position: Position,
+    // Update ticks until it hits.
+    pub warning: u64,
+    // Update ticks it keeps going after it hit.
+    pub remaining: u64,
+}
+
+impl Hazard {
+    pub fn new(kind: HazardKind, position: Position, warning: u64) -> Hazard {
+        let remaining = kind.duration();
+
+        return Hazard {
+            kind,
+            position,
+            warning,
+            remaining,
+        };
+    }
+
+    pub fn is_active(&self) -> bool {
+        return self.warning == 0 && self.remaining > 0;
+    }
+
+    pub fn covers(&self, position: &Position) -> bool {
+        return self.position.distance(position) <= self.kind.radius();
+    }
+}
@@ -4,10 +4,16 @@ extern crate itertools;
 extern crate worldgen;
 
 mod component;
+mod errors;
 mod game;
 mod gui;
+mod hazards;
 mod managers;
+mod pathfinding;
+mod pollution;
+mod rovers;
 mod structures;
+mod terraforming;
 mod util;
 
 use std::error::Error;
@@ -15,8 +21,8 @@ use std::io;
 use std::time::{Duration, SystemTime};
 
 use tui::backend::CrosstermBackend;
-use tui::layout::Margin;
-use tui::widgets::Paragraph;
+use tui::layout::{Margin, Rect};
+use tui::widgets::{Paragraph, TableState};
 use tui::Terminal;
 
 use crossterm::event::{poll, read, Event, KeyCode};
@@ -24,17 +30,32 @@ use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
 use worldgen::world::Size;
 
-use crate::game::{Commodity, Manufactured, MapController, Resource};
+use crate::component::ComponentName;
+use crate::errors::RetoolError;
+use crate::game::{Commodity, DayCycle, Manufactured, MapController, PlanetType, Resource};
 use crate::gui::{
-    FactoryCommoditySelect, Menu, MenuSelector, MineResourceSelect, RefineryResourceSelect,
+    FactoryCommoditySelect, InfoContext, MapMode, Menu, MenuSelector, MetricSelect,
+    MineResourceSelect, PlacementPreview, RefineryResourceSelect, Screen, StructureList,
+    TerraformProjectSelect,
 };
-use crate::managers::{EnergyManager, ResourceManager};
-use crate::structures::{StructureFactory, StructureGroup};
+use crate::managers::{
+    BottleneckManager, EnergyManager, HazardManager, LogisticsManager, Metric, PollutionManager,
+    ResourceManager, RoverManager, StatisticsManager, SurveyManager,
+};
+use crate::rovers::RoverKind;
+use crate::structures::{BatteryTrait, StructureFactory, StructureGroup};
+use crate::terraforming::TerraformProject;
 
 use crate::util::format_welcome_message;
 use crate::util::{EventBus, GameEvent, Tick};
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // The planet preset can be picked with the first argument, e.g. `exo-colony frozen`.
+    let planet = match std::env::args().nth(1) {
+        Some(name) => name.parse::<PlanetType>()?,
+        None => PlanetType::Temperate,
+    };
+
     let stdout = io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
@@ -56,6 +77,8 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut energy_manager = EnergyManager::new();
 
+    let mut survey_manager = SurveyManager::new();
+
     // An object for player score keeping and updating.
     let storage_resources = vec![
         Resource::Iron,
@@ -88,13 +111,48 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut resource_manager =
         ResourceManager::new(storage_resources, storage_manufactured, storage_commodities);
 
+    let mut statistics_manager = StatisticsManager::new(&resource_manager);
+    let mut metric_select = MetricSelect::new(statistics_manager.metrics().clone());
+
+    let mut bottleneck_manager = BottleneckManager::new();
+
+    let mut logistics_manager = LogisticsManager::new();
+
+    let mut rover_manager = RoverManager::new();
+
+    let mut hazard_manager = HazardManager::new();
+
+    let mut pollution_manager = PollutionManager::new();
+    let mut rover_kind = RoverKind::Survey;
+
     let mut menu = Menu::new(vec![
         StructureGroup::Base,
         StructureGroup::Power,
+        StructureGroup::Solar,
         StructureGroup::Mine,
         StructureGroup::Refinery,
         StructureGroup::Factory,
         StructureGroup::Storage,
+        StructureGroup::Radar,
+        StructureGroup::Road,
+        StructureGroup::Shield,
+        StructureGroup::Terraformer,
+        StructureGroup::Scrubber,
+    ]);
+
+    let mut structure_list = StructureList::new(vec![
+        StructureGroup::Base,
+        StructureGroup::Power,
+        StructureGroup::Solar,
+        StructureGroup::Mine,
+        StructureGroup::Refinery,
+        StructureGroup::Factory,
+        StructureGroup::Storage,
+        StructureGroup::Radar,
+        StructureGroup::Road,
+        StructureGroup::Shield,
+        StructureGroup::Terraformer,
+        StructureGroup::Scrubber,
     ]);
 
     let mut mine_select = MineResourceSelect::new(vec![
@@ -123,8 +181,12 @@ fn main() -> Result<(), Box<dyn Error>> {
         Commodity::FuelRod,
     ]);
 
+    let mut terraform_select = TerraformProjectSelect::new(TerraformProject::all());
+
+    log_buffer.push_str(&util::get_log(format!("Landing on a {} planet.", planet)));
+
     // The game controller, work with the Map object.
-    let mut controller = MapController::new(Size::of(90, 40));
+    let mut controller = MapController::new(Size::of(90, 40), planet);
 
     // Default margin used when drawing interfaces.
     let margin_1 = Margin {
@@ -134,13 +196,89 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut map_widget: Option<Paragraph> = None;
 
+    let mut map_mode = MapMode::Terrain;
+
+    // The overview shows the whole map at once, and moves the cursor in larger steps.
+    let mut overview = false;
+    let mut map_viewport = Rect::default();
+
+    let mut screen = Screen::Game;
+
     controller.generate_deposits();
+    controller.explore_landing();
 
     loop {
         let elapsed = now.elapsed()?;
         let game_event = events.next()?;
 
+        let preview = PlacementPreview::new(
+            menu.selected(),
+            StructureFactory::validate(
+                &menu.selected(),
+                &controller.position(),
+                &controller,
+                &resource_manager,
+                &rover_manager,
+                &logistics_manager,
+            ),
+            StructureFactory::cost(&menu.selected(), &controller.position(), &controller),
+        );
+
         terminal.draw(|frame| {
+            if screen == Screen::Statistics {
+                let metric = metric_select.selected();
+                let statistics_layout = gui::build_statistics_layout(frame.size());
+                let graph_layout = gui::build_graph_layout(statistics_layout[1]);
+
+                let statistics_list =
+                    gui::draw_statistics_list_widget(&statistics_manager, &metric_select);
+                frame.render_widget(statistics_list, statistics_layout[0]);
+
+                let points = statistics_manager.points(&metric);
+                let chart = gui::draw_statistics_chart_widget(
+                    &metric,
+                    &points,
+                    statistics_manager.time_bounds(),
+                    statistics_manager.income_per_minute(&metric),
+                );
+                frame.render_widget(chart, graph_layout[0]);
+
+                let energy_metrics = [
+                    Metric::EnergyOutput,
+                    Metric::EnergyStored,
+                    Metric::EnergyDeficit,
+                ];
+                for (index, energy_metric) in energy_metrics.iter().enumerate() {
+                    let area = graph_layout[index + 1];
+                    let values = statistics_manager.values(energy_metric);
+                    let sparkline = gui::draw_sparkline_widget(energy_metric, &values, area.width);
+                    frame.render_widget(sparkline, area);
+                }
+
+                return;
+            }
+
+            if screen == Screen::Structures {
+                let rows = structure_list.rows(controller.objects());
+
+                let mut table_state = TableState::default();
+                table_state.select(Some(structure_list.selected(rows.len())));
+
+                let structure_table = gui::draw_structure_table_widget(&rows, &structure_list);
+                frame.render_stateful_widget(
+                    structure_table,
+                    frame.size().inner(&margin_1),
+                    &mut table_state,
+                );
+                return;
+            }
+
+            if screen == Screen::Bottlenecks {
+                let bottleneck_widget = gui::draw_bottleneck_widget(&bottleneck_manager);
+                frame.render_widget(bottleneck_widget, frame.size().inner(&margin_1));
+                return;
+            }
+
             let main_layout = gui::build_main_layout(frame.size());
             let left_layout = gui::build_left_layout(main_layout[0]);
             let right_layout = gui::build_right_layout(main_layout[2]);
@@ -150,6 +288,8 @@ fn main() -> Result<(), Box<dyn Error>> {
             let stats_widget_left = gui::draw_stats_widget_left(
                 &resource_manager,
                 &energy_manager,
+                controller.habitable_share(),
+                &pollution_manager,
                 elapsed,
                 update_tick.delta(),
                 draw_tick.delta(),
@@ -169,6 +309,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             match menu.selected() {
                 StructureGroup::Base => {}
                 StructureGroup::Power => {}
+                StructureGroup::Solar => {}
                 StructureGroup::Mine => {
                     // let resource_select_widget = gui::draw_mine_select_widget(&mine_select);
                     // frame.render_widget(resource_select_widget, menu_layout[1]);
@@ -183,19 +324,39 @@ fn main() -> Result<(), Box<dyn Error>> {
                     frame.render_widget(commodity_select_widget, menu_layout[1]);
                 }
                 StructureGroup::Storage => {}
+                StructureGroup::Radar => {}
+                StructureGroup::Road => {}
+                StructureGroup::Shield => {}
+                StructureGroup::Terraformer => {
+                    let project_select_widget =
+                        gui::draw_terraform_select_widget(&terraform_select);
+                    frame.render_widget(project_select_widget, menu_layout[1]);
+                }
+                StructureGroup::Scrubber => {}
             }
 
-            let info_panel = gui::draw_info_widget(
-                controller.position(),
-                controller.tile(),
-                controller.object(),
-            );
+            let info_panel = gui::draw_info_widget(InfoContext {
+                position: controller.position(),
+                tile: controller.tile(),
+                object: controller.object(),
+                slope: controller.map().slope(&controller.position()),
+                survey: survey_manager.progress(&controller.position()),
+                preview: &preview,
+                mode: &map_mode,
+                storage: &resource_manager,
+                route: controller
+                    .objects()
+                    .owner(&controller.position())
+                    .and_then(|anchor| logistics_manager.route(anchor)),
+                rovers: &rover_manager,
+                hazards: &hazard_manager,
+            });
             frame.render_widget(info_panel, right_layout[1]);
 
-            let map_block = gui::draw_map_block();
+            let map_block = gui::draw_map_block(&map_mode, overview);
             frame.render_widget(map_block, main_layout[1]);
 
-            let map_viewport = main_layout[1].inner(&margin_1);
+            map_viewport = main_layout[1].inner(&margin_1);
 
             // If the widget was drawn by the draw event, render it, otherwise do not.
             if map_widget.is_some() {
@@ -207,12 +368,40 @@ fn main() -> Result<(), Box<dyn Error>> {
             // When we get the draw event, we'll update the game map.
             // Map will not be drawn every loop iteration.
             GameEvent::Update => {
+                for message in hazard_manager.update(&mut controller, &rover_manager) {
+                    log_buffer.push_str(&util::get_log(message));
+                }
+
+                for message in controller.terraform() {
+                    log_buffer.push_str(&util::get_log(message));
+                }
+
+                controller.update_climate();
                 energy_manager.zero();
 
-                energy_manager.collect(controller.objects_mut().list());
+                let day = DayCycle::at(elapsed);
+                energy_manager.collect(controller.objects().list(), &hazard_manager, &day);
+
+                // goods arriving this tick can be used right away.
+                logistics_manager.update_routes(&controller, &rover_manager);
+                logistics_manager.deliver(controller.objects_mut(), &mut resource_manager);
 
                 let objects = controller.objects_mut().list_mut();
-                resource_manager.collect(objects, &mut energy_manager);
+                resource_manager.collect(objects, &mut energy_manager, &mut logistics_manager);
+                logistics_manager.dispatch(controller.objects_mut().list_mut());
+
+                // surveys and rovers draw on this tick's energy, before batteries settle up.
+                for (position, deposit) in
+                    survey_manager.update(&mut controller, &mut energy_manager)
+                {
+                    let message = format!(
+                        "Survey at {} complete: {} {}",
+                        position, deposit.resource, deposit.amount
+                    );
+                    log_buffer.push_str(&util::get_log(message));
+                }
+
+                rover_manager.update(&mut controller, &mut energy_manager);
 
                 // if we discharged energy from storage, discharge batteries.
                 if energy_manager.discharged() > 0 {
@@ -224,14 +413,37 @@ fn main() -> Result<(), Box<dyn Error>> {
                     energy_manager.charge(controller.objects_mut().list_mut());
                 }
 
+                controller.explore();
+
+                for message in pollution_manager.update(&mut controller) {
+                    log_buffer.push_str(&util::get_log(message));
+                }
+
+                statistics_manager.record(elapsed, &resource_manager, &energy_manager);
+                bottleneck_manager.record(controller.objects().list(), &energy_manager);
+
                 update_tick.update(&elapsed);
             }
             GameEvent::Draw => {
-                let map_text = gui::render_map(
-                    controller.map(),
-                    controller.objects(),
-                    controller.position(),
-                );
+                let map_text = if overview {
+                    gui::render_overview(
+                        controller.map(),
+                        controller.objects(),
+                        controller.position(),
+                        gui::overview_scale(controller.map(), map_viewport),
+                        DayCycle::at(elapsed).light(),
+                    )
+                } else {
+                    gui::render_map(
+                        controller.map(),
+                        controller.objects(),
+                        controller.position(),
+                        &preview,
+                        &map_mode,
+                        &rover_manager,
+                        DayCycle::at(elapsed).light(),
+                    )
+                };
                 map_widget = Option::from(gui::draw_map_widget(&map_text));
                 draw_tick.update(&elapsed);
             }
@@ -243,42 +455,155 @@ fn main() -> Result<(), Box<dyn Error>> {
                         Event::Key(event) => {
                             // let log = util::get_log(format!("{:?}", event));
                             // log_buffer.push_str(&log);
+                            let step = if overview {
+                                gui::overview_scale(controller.map(), map_viewport)
+                            } else {
+                                1
+                            };
+
+                            if screen != Screen::Game {
+                                match event.code {
+                                    KeyCode::Up if screen == Screen::Statistics => {
+                                        metric_select.previous()
+                                    }
+                                    KeyCode::Down if screen == Screen::Statistics => {
+                                        metric_select.next()
+                                    }
+                                    KeyCode::Up if screen == Screen::Structures => {
+                                        let count = structure_list.rows(controller.objects()).len();
+                                        structure_list.previous(count);
+                                    }
+                                    KeyCode::Down if screen == Screen::Structures => {
+                                        let count = structure_list.rows(controller.objects()).len();
+                                        structure_list.next(count);
+                                    }
+                                    KeyCode::Enter if screen == Screen::Structures => {
+                                        let rows = structure_list.rows(controller.objects());
+                                        let index = structure_list.selected(rows.len());
+
+                                        // Jump to the structure on the map.
+                                        if let Some(row) = rows.get(index) {
+                                            controller.jump(&row.position);
+                                            screen = Screen::Game;
+                                        }
+                                    }
+                                    KeyCode::Char(c) if screen == Screen::Structures => match c {
+                                        's' => structure_list.next_sort(),
+                                        'f' => structure_list.next_filter(),
+                                        ' ' => {
+                                            let rows = structure_list.rows(controller.objects());
+                                            let index = structure_list.selected(rows.len());
+
+                                            if let Some(row) = rows.get(index) {
+                                                controller.set_enabled(&row.position, !row.enabled);
+                                            }
+                                        }
+                                        't' => {
+                                            // Bulk toggle, if anything listed is running, switch everything listed off.
+                                            let rows = structure_list.rows(controller.objects());
+                                            let enabled = !rows.iter().any(|row| row.enabled);
+
+                                            for row in rows.iter() {
+                                                controller.set_enabled(&row.position, enabled);
+                                            }
+
+                                            let message = format!(
+                                                "{} {} listed structure(s)",
+                                                if enabled { "Enabled" } else { "Disabled" },
+                                                rows.len()
+                                            );
+                                            log_buffer.push_str(&util::get_log(message));
+                                        }
+                                        _ => {}
+                                    },
+                                    KeyCode::Tab => screen = screen.next(),
+                                    KeyCode::BackTab => screen = screen.previous(),
+                                    KeyCode::Esc => screen = Screen::Game,
+                                    _ => {}
+                                }
+                                continue;
+                            }
+
                             match event.code {
                                 KeyCode::Backspace => {}
                                 KeyCode::Left => {
-                                    controller.left();
+                                    for _ in 0..step {
+                                        controller.left();
+                                    }
+                                }
+                                KeyCode::Enter if overview => {
+                                    // Jump back to the map at the picked location.
+                                    overview = false;
                                 }
                                 KeyCode::Enter => {
                                     let structure_group = menu.selected();
-                                    let tile = controller.tile();
-                                    let object = controller.object();
-
-                                    if StructureFactory::allowed(&structure_group, tile) {
-                                        let structure = StructureFactory::new(
+                                    let position = controller.position();
+                                    // Priced before placing, a new base changes what the next one costs.
+                                    let cost = StructureFactory::cost(
+                                        &structure_group,
+                                        &position,
+                                        &controller,
+                                    );
+
+                                    let result = StructureFactory::validate(
+                                        &structure_group,
+                                        &position,
+                                        &controller,
+                                        &resource_manager,
+                                        &rover_manager,
+                                        &logistics_manager,
+                                    )
+                                    .and_then(|_| {
+                                        StructureFactory::new(
                                             &structure_group,
-                                            object,
+                                            controller.tile(),
+                                            controller.object(),
                                             &resource_manager,
                                             &refinery_select,
                                             &factory_select,
-                                        );
-
-                                        if structure.is_some() {
-                                            controller.add_structure(structure.unwrap());
+                                            &terraform_select,
+                                        )
+                                    })
+                                    .and_then(
+                                        |mut structure| {
+                                            structure.blueprint_mut().set_cost(cost.clone());
+                                            controller.add_structure(structure)
+                                        },
+                                    );
+
+                                    match result {
+                                        Ok(_) => {
+                                            resource_manager.withdraw_resources(
+                                                &cost,
+                                                controller.objects_mut(),
+                                                &logistics_manager,
+                                            );
+                                        }
+                                        Err(error) => {
+                                            let message = format!("Cannot build: {}", error);
+                                            log_buffer.push_str(&util::get_log(message));
                                         }
                                     }
                                 }
                                 KeyCode::Right => {
-                                    controller.right();
+                                    for _ in 0..step {
+                                        controller.right();
+                                    }
                                 }
                                 KeyCode::Up => {
-                                    controller.up();
+                                    for _ in 0..step {
+                                        controller.up();
+                                    }
                                 }
                                 KeyCode::Down => {
-                                    controller.down();
+                                    for _ in 0..step {
+                                        controller.down();
+                                    }
                                 }
                                 KeyCode::Home => match menu.selected() {
                                     StructureGroup::Base => {}
                                     StructureGroup::Power => {}
+                                    StructureGroup::Solar => {}
                                     StructureGroup::Mine => {
                                         mine_select.previous();
                                     }
@@ -289,10 +614,18 @@ fn main() -> Result<(), Box<dyn Error>> {
                                         factory_select.previous();
                                     }
                                     StructureGroup::Storage => {}
+                                    StructureGroup::Radar => {}
+                                    StructureGroup::Road => {}
+                                    StructureGroup::Shield => {}
+                                    StructureGroup::Terraformer => {
+                                        terraform_select.previous();
+                                    }
+                                    StructureGroup::Scrubber => {}
                                 },
                                 KeyCode::End => match menu.selected() {
                                     StructureGroup::Base => {}
                                     StructureGroup::Power => {}
+                                    StructureGroup::Solar => {}
                                     StructureGroup::Mine => {
                                         mine_select.next();
                                     }
@@ -303,6 +636,13 @@ fn main() -> Result<(), Box<dyn Error>> {
                                         factory_select.next();
                                     }
                                     StructureGroup::Storage => {}
+                                    StructureGroup::Radar => {}
+                                    StructureGroup::Road => {}
+                                    StructureGroup::Shield => {}
+                                    StructureGroup::Terraformer => {
+                                        terraform_select.next();
+                                    }
+                                    StructureGroup::Scrubber => {}
                                 },
                                 KeyCode::PageUp => {
                                     menu.previous();
@@ -310,25 +650,168 @@ fn main() -> Result<(), Box<dyn Error>> {
                                 KeyCode::PageDown => {
                                     menu.next();
                                 }
-                                KeyCode::Tab => {}
-                                KeyCode::BackTab => {}
+                                KeyCode::Tab => {
+                                    screen = screen.next();
+                                }
+                                KeyCode::BackTab => {
+                                    screen = screen.previous();
+                                }
                                 KeyCode::Delete => {
-                                    controller.destroy_structure();
+                                    let position = controller.position();
+
+                                    if let Some(structure) = controller.destroy_structure() {
+                                        let salvaged = resource_manager.salvage(&structure);
+
+                                        let blueprint = structure.blueprint();
+                                        let mut recovered = 0;
+                                        if blueprint.has_component(&ComponentName::BatteryComponent)
+                                        {
+                                            recovered = energy_manager.redistribute(
+                                                BatteryTrait::stored(blueprint),
+                                                controller.objects_mut().list_mut(),
+                                            );
+                                        }
+
+                                        let message = format!(
+                                            "Demolished {} at {}, salvaged {} and {} energy",
+                                            structure,
+                                            position,
+                                            util::format_resources(&salvaged),
+                                            recovered
+                                        );
+                                        log_buffer.push_str(&util::get_log(message));
+                                    }
                                 }
                                 KeyCode::Insert => {}
                                 KeyCode::F(_) => {}
                                 KeyCode::Char(c) => match c {
                                     'a' => {
-                                        controller.left();
+                                        for _ in 0..step {
+                                            controller.left();
+                                        }
                                     }
                                     'd' => {
-                                        controller.right();
+                                        for _ in 0..step {
+                                            controller.right();
+                                        }
                                     }
                                     'w' => {
-                                        controller.up();
+                                        for _ in 0..step {
+                                            controller.up();
+                                        }
                                     }
                                     's' => {
-                                        controller.down();
+                                        for _ in 0..step {
+                                            controller.down();
+                                        }
+                                    }
+                                    'm' => {
+                                        map_mode = map_mode.next();
+                                    }
+                                    'o' => {
+                                        overview = !overview;
+                                    }
+                                    'b' => {
+                                        let report = bottleneck_manager.report();
+
+                                        if report.is_empty() {
+                                            let message = String::from(
+                                                "No bottlenecks, every structure is running",
+                                            );
+                                            log_buffer.push_str(&util::get_log(message));
+                                        }
+
+                                        // The console shows the newest line first, so log the worst one last.
+                                        for (index, entry) in
+                                            report.iter().take(3).enumerate().rev()
+                                        {
+                                            let message = gui::format_bottleneck(index + 1, entry);
+                                            log_buffer.push_str(&util::get_log(message));
+                                        }
+                                    }
+                                    'r' => {
+                                        let position = controller.position();
+
+                                        let result = match controller.structure_mut() {
+                                            Some(structure) => StructureFactory::retool(
+                                                structure,
+                                                &refinery_select,
+                                                &factory_select,
+                                                &terraform_select,
+                                            )
+                                            .map(|recipe| (structure.to_string(), recipe)),
+                                            None => Err(RetoolError::NoStructure {
+                                                position: position.clone(),
+                                            }),
+                                        };
+
+                                        let message = match result {
+                                            Ok((structure, recipe)) => format!(
+                                                "Retooling {} at {} to make {}",
+                                                structure, position, recipe
+                                            ),
+                                            Err(error) => format!("Cannot retool: {}", error),
+                                        };
+                                        log_buffer.push_str(&util::get_log(message));
+                                    }
+                                    'k' => {
+                                        rover_kind = rover_kind.next();
+
+                                        let message = format!("Next rover: {}", rover_kind);
+                                        log_buffer.push_str(&util::get_log(message));
+                                    }
+                                    'u' => {
+                                        let message = match rover_manager.spawn(
+                                            rover_kind,
+                                            &mut controller,
+                                            &mut resource_manager,
+                                            &logistics_manager,
+                                        ) {
+                                            Ok(rover) => format!(
+                                                "{} #{} rolled out at {}",
+                                                rover.kind(),
+                                                rover.id(),
+                                                rover.position()
+                                            ),
+                                            Err(error) => format!("Cannot build rover: {}", error),
+                                        };
+                                        log_buffer.push_str(&util::get_log(message));
+                                    }
+                                    'e' => {
+                                        let message = match rover_manager
+                                            .select(&controller.position())
+                                        {
+                                            Ok(rover) => {
+                                                format!("Selected {} #{}", rover.kind(), rover.id())
+                                            }
+                                            Err(error) => format!("Cannot select: {}", error),
+                                        };
+                                        log_buffer.push_str(&util::get_log(message));
+                                    }
+                                    'g' => {
+                                        let position = controller.position();
+
+                                        let message =
+                                            match rover_manager.order(&position, &controller) {
+                                                Ok(rover) => format!(
+                                                    "{} #{} heading to {}",
+                                                    rover.kind(),
+                                                    rover.id(),
+                                                    rover.destination().unwrap_or(&position)
+                                                ),
+                                                Err(error) => format!("Cannot move: {}", error),
+                                            };
+                                        log_buffer.push_str(&util::get_log(message));
+                                    }
+                                    'v' => {
+                                        let position = controller.position();
+
+                                        let message =
+                                            match survey_manager.start(&position, &controller) {
+                                                Ok(_) => format!("Survey started at {}", position),
+                                                Err(error) => format!("Cannot survey: {}", error),
+                                            };
+                                        log_buffer.push_str(&util::get_log(message));
                                     }
                                     _ => {}
                                 },
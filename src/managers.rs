@@ -1,13 +1,34 @@
 use std::collections::hash_map::{Iter, IterMut};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
 use std::ops::{AddAssign, SubAssign};
+use std::time::Duration;
 
 use crate::component::{ComponentGroup, ComponentName};
-use crate::game::{Commodity, Manufactured, MapObject, Position, Resource};
+use crate::errors::{RoverError, SurveyError};
+use crate::game::{
+    Commodity, DayCycle, Manufactured, MapController, MapObject, ObjectManager, Position, Resource,
+    ResourceDeposit,
+};
+use crate::hazards::{
+    Hazard, HazardKind, DUST_STORM_OUTPUT, FAILURE_REPAIR_TICKS, METEOR_REPAIR_TICKS,
+    SOLAR_FLARE_DRAIN,
+};
+use crate::pathfinding::{Pathfinder, Traversal};
+use crate::pollution::{
+    HEALTHY_POLLUTION, HEALTH_LOSS, HEALTH_RECOVERY, SCRUBBER_RADIUS, SCRUBBER_RATE,
+};
+use crate::rovers::{Rover, RoverKind, RoverStatus, ROVER_CHARGE_RATE, ROVER_MOVE_ENERGY};
 use crate::structures::{
-    BatteryTrait, EnergyTrait, MineOutputTrait, ResourceStorageTrait, Structure,
+    BatteryTrait, CommodityStorageTrait, EnergyTrait, MineOutputTrait, ProductionTrait,
+    ResourceStorageTrait, SightTrait, Structure, StructureBlueprint, StructureCostFactory,
+    StructureGroup, StructureGroupTrait, StructureStatus, INPUT_BUFFER_CYCLES, SHIELD_RADIUS,
 };
 
+use crate::util::UPDATE_RATE_MS;
+use itertools::Itertools;
+use rand::prelude::SliceRandom;
+use rand::Rng;
 use std::iter::FromIterator;
 
 pub struct EnergyManager {
@@ -15,6 +36,11 @@ pub struct EnergyManager {
     stored: u64,
     discharged: u64,
     deficit: u64,
+    // Strength of the sun on the last collect, between 0.0 and 1.0.
+    sunlight: f64,
+    // Output and stored energy as collected, before anything was drawn from them.
+    produced: u64,
+    reserve: u64,
 }
 
 impl EnergyManager {
@@ -23,12 +49,18 @@ impl EnergyManager {
         let stored = 0;
         let discharged = 0;
         let deficit = 0;
+        let sunlight = 0.0;
+        let produced = 0;
+        let reserve = 0;
 
         return EnergyManager {
             output,
             stored,
             discharged,
             deficit,
+            sunlight,
+            produced,
+            reserve,
         };
     }
 
@@ -48,6 +80,18 @@ impl EnergyManager {
         self.deficit
     }
 
+    pub fn sunlight(&self) -> f64 {
+        self.sunlight
+    }
+
+    pub fn produced(&self) -> u64 {
+        self.produced
+    }
+
+    pub fn reserve(&self) -> u64 {
+        self.reserve
+    }
+
     pub fn combined(&self) -> u64 {
         self.output + self.stored
     }
@@ -63,9 +107,20 @@ impl EnergyManager {
         self.output() >= amount || self.combined() >= amount
     }
 
-    pub fn collect(&mut self, objects: Iter<Position, MapObject>) {
-        let filtered = objects.filter(|(_, o)| o.structure.is_some());
-        for (_, object) in filtered {
+    pub fn collect(
+        &mut self,
+        objects: Iter<Position, MapObject>,
+        hazards: &HazardManager,
+        day: &DayCycle,
+    ) {
+        self.sunlight = day.sunlight();
+
+        let filtered = objects.filter(|(_, o)| {
+            o.structure
+                .as_ref()
+                .is_some_and(|s| s.blueprint().is_enabled() && !s.blueprint().is_damaged())
+        });
+        for (position, object) in filtered {
             let structure = object.structure.as_ref().unwrap();
 
             match structure {
@@ -74,11 +129,22 @@ impl EnergyManager {
                     self.stored.add_assign(structure.blueprint().stored());
                 }
                 Structure::PowerPlant { structure } => {
-                    self.output.add_assign(structure.blueprint().energy_out());
+                    let output =
+                        structure.blueprint().energy_out() * hazards.output_percent(position) / 100;
+                    self.output.add_assign(output);
+                }
+                // Solar panels follow the sun, at night only the batteries are left.
+                Structure::SolarPanel { structure } => {
+                    let output = structure.blueprint().energy_out() as f64 * self.sunlight;
+                    let output = output.round() as u64 * hazards.output_percent(position) / 100;
+                    self.output.add_assign(output);
                 }
                 _ => {}
             }
         }
+
+        self.produced = self.output;
+        self.reserve = self.stored;
     }
 
     pub fn withdraw_output(&mut self, amount: u64) -> u64 {
@@ -179,6 +245,28 @@ impl EnergyManager {
         }
     }
 
+    // Moves energy salvaged from a demolished battery into the remaining batteries.
+    // Returns the amount that found free capacity, the rest is lost.
+    pub fn redistribute(&mut self, amount: u64, objects: IterMut<Position, MapObject>) -> u64 {
+        let mut remaining = amount;
+        let filtered = objects.filter(|(_, o)| o.structure.is_some());
+
+        for (_, object) in filtered {
+            if remaining == 0 {
+                break;
+            }
+
+            let blueprint = object.structure.as_mut().unwrap().blueprint_mut();
+
+            if blueprint.has_component(&ComponentName::BatteryComponent) {
+                let charged = BatteryTrait::charge(blueprint, remaining);
+                remaining.sub_assign(charged);
+            }
+        }
+
+        return amount - remaining;
+    }
+
     pub fn discharge(&mut self, objects: IterMut<Position, MapObject>) {
         let filtered = objects.filter(|(_, o)| o.structure.is_some());
 
@@ -202,6 +290,13 @@ impl EnergyManager {
     }
 }
 
+// Inputs and energy one production cycle takes.
+struct Recipe {
+    resources: HashMap<Resource, u64>,
+    manufactured: HashMap<Manufactured, u64>,
+    energy: u64,
+}
+
 pub struct ResourceManager {
     resources: HashMap<Resource, u64>,
     resources_deficit: HashMap<Resource, u64>,
@@ -269,9 +364,8 @@ impl ResourceManager {
         return self.resources.iter_mut();
     }
 
-    pub fn has_resource(&self, resource_type: &Resource, amount: u64) -> bool {
-        let available = self.resources.get(resource_type).unwrap();
-        return *available > amount;
+    pub fn resource(&self, resource_type: &Resource) -> u64 {
+        return self.resources.get(resource_type).copied().unwrap_or(0);
     }
 
     pub fn deposit_resource(&mut self, resource_type: &Resource, amount: u64) -> u64 {
@@ -293,6 +387,73 @@ impl ResourceManager {
         return amount;
     }
 
+    // Resources put away in working storages count towards the colony stock.
+    pub fn has_resources(
+        &self,
+        required: &HashMap<Resource, u64>,
+        objects: &ObjectManager,
+        logistics: &LogisticsManager,
+    ) -> bool {
+        let stored = Self::stored_resources(objects, logistics);
+
+        return required.iter().all(|(resource, amount)| {
+            let available = self.resources.get(resource).unwrap();
+            available + stored.get(resource).copied().unwrap_or(0) >= *amount
+        });
+    }
+
+    fn stored_resources(
+        objects: &ObjectManager,
+        logistics: &LogisticsManager,
+    ) -> HashMap<Resource, u64> {
+        let mut stored = HashMap::new();
+
+        for (position, object) in objects.list() {
+            if let Some(Structure::Storage { structure }) = &object.structure {
+                let blueprint = structure.blueprint();
+                if !Self::trades_stock(position, blueprint, logistics) {
+                    continue;
+                }
+
+                for resource in ResourceStorageTrait::resources(blueprint) {
+                    let amount = ResourceStorageTrait::resource(blueprint, resource);
+                    stored.entry(*resource).or_insert(0).add_assign(amount);
+                }
+            }
+        }
+
+        return stored;
+    }
+
+    pub fn deposit_resources(&mut self, resources: &HashMap<Resource, u64>) {
+        for (resource, amount) in resources {
+            self.deposit_resource(resource, *amount);
+        }
+    }
+
+    // Takes from working storages first and the rest from the colony stock.
+    pub fn withdraw_resources(
+        &mut self,
+        resources: &HashMap<Resource, u64>,
+        objects: &mut ObjectManager,
+        logistics: &LogisticsManager,
+    ) {
+        for (resource, amount) in resources {
+            let mut left = *amount;
+
+            for (position, object) in objects.list_mut() {
+                if let Some(Structure::Storage { structure }) = &mut object.structure {
+                    let blueprint = structure.blueprint_mut();
+                    if Self::trades_stock(position, blueprint, logistics) {
+                        left -= blueprint.resource_take(resource, left);
+                    }
+                }
+            }
+
+            self.withdraw_resource(resource, left);
+        }
+    }
+
     fn add_resource_deficit(&mut self, resource_type: &Resource, amount: u64) {
         self.resources_deficit
             .get_mut(&resource_type)
@@ -397,6 +558,64 @@ impl ResourceManager {
         return self.commodities_deficit.get(resource_type).unwrap().clone();
     }
 
+    // Returns the refunded share of the construction cost and everything
+    // held in the structure storage back into colony stock.
+    pub fn salvage(&mut self, structure: &Structure) -> HashMap<Resource, u64> {
+        let blueprint = structure.blueprint();
+        let mut salvaged = StructureCostFactory::refund_for_cost(blueprint.cost());
+
+        // inputs waiting in a production buffer are returned in full.
+        for resource in blueprint.buffered_resources() {
+            let amount = blueprint.buffered(&resource);
+            if amount > 0 {
+                salvaged.entry(resource).or_insert(0).add_assign(amount);
+            }
+        }
+
+        // and so are the ones a running cycle already took.
+        for (resource, amount) in blueprint.cycle_inputs() {
+            salvaged.entry(resource).or_insert(0).add_assign(amount);
+        }
+
+        if blueprint.has_component(&ComponentName::ResourceStorageComponent) {
+            for resource in ResourceStorageTrait::resources(blueprint) {
+                let amount = ResourceStorageTrait::resource(blueprint, resource);
+                if amount > 0 {
+                    salvaged.entry(*resource).or_insert(0).add_assign(amount);
+                }
+            }
+        }
+
+        self.deposit_resources(&salvaged);
+
+        for manufactured in blueprint.buffered_manufactured_types() {
+            let amount = blueprint.buffered_manufactured(&manufactured);
+            self.deposit_manufactured(&manufactured, amount);
+        }
+
+        for (manufactured, amount) in blueprint.cycle_manufactured() {
+            self.deposit_manufactured(&manufactured, amount);
+        }
+
+        // finished goods still waiting to be shipped.
+        for (manufactured, amount) in blueprint.finished_manufactured() {
+            self.deposit_manufactured(&manufactured, amount);
+        }
+
+        for (commodity, amount) in blueprint.finished_commodities() {
+            self.deposit_commodity(&commodity, amount);
+        }
+
+        if blueprint.has_component(&ComponentName::CommodityStorageComponent) {
+            for commodity in CommodityStorageTrait::commodities(blueprint) {
+                let amount = CommodityStorageTrait::commodity(blueprint, commodity);
+                self.deposit_commodity(commodity, amount);
+            }
+        }
+
+        return salvaged;
+    }
+
     fn zero_deficit(&mut self) {
         for (_, deficit) in self.resources_deficit.iter_mut() {
             *deficit = 0;
@@ -411,37 +630,205 @@ impl ResourceManager {
         }
     }
 
+    // Tops up the input buffer of a producer and runs one tick of its production cycle.
+    // Progress is kept while the producer waits for energy, inputs are paid when a cycle starts.
+    fn produce(
+        &mut self,
+        position: &Position,
+        blueprint: &mut StructureBlueprint,
+        recipe: &Recipe,
+        energy_manager: &mut EnergyManager,
+        logistics: &mut LogisticsManager,
+    ) -> StructureStatus {
+        let required = &recipe.resources;
+        let required_manufactured = &recipe.manufactured;
+        let ticks = logistics.route(position).map_or(0, |route| route.ticks);
+        let destination = Destination::Buffer(position.clone());
+
+        // hand back inputs left over from a previous recipe.
+        for resource in blueprint.buffered_resources() {
+            if !required.contains_key(&resource) {
+                let amount = blueprint.buffer_take(&resource);
+                logistics.ship(Cargo::Resource(resource), amount, Destination::Stock, ticks);
+            }
+        }
+
+        for manufactured in blueprint.buffered_manufactured_types() {
+            if !required_manufactured.contains_key(&manufactured) {
+                let amount = blueprint.buffer_take_manufactured(&manufactured);
+                logistics.ship(
+                    Cargo::Manufactured(manufactured),
+                    amount,
+                    Destination::Stock,
+                    ticks,
+                );
+            }
+        }
+
+        // order inputs from stock, counting what is already on the road.
+        for (resource, amount) in required {
+            let target = amount * INPUT_BUFFER_CYCLES;
+            let incoming = logistics.in_transit(&destination, &Cargo::Resource(*resource));
+            let buffered = blueprint.buffered(resource) + incoming;
+
+            if buffered < target {
+                let withdrawn = self.withdraw_resource(resource, target - buffered);
+                logistics.ship(
+                    Cargo::Resource(*resource),
+                    withdrawn,
+                    destination.clone(),
+                    ticks,
+                );
+            }
+        }
+
+        for (manufactured, amount) in required_manufactured {
+            let target = amount * INPUT_BUFFER_CYCLES;
+            let incoming = logistics.in_transit(&destination, &Cargo::Manufactured(*manufactured));
+            let buffered = blueprint.buffered_manufactured(manufactured) + incoming;
+
+            if buffered < target {
+                let withdrawn = self.withdraw_manufactured(manufactured, target - buffered);
+                logistics.ship(
+                    Cargo::Manufactured(*manufactured),
+                    withdrawn,
+                    destination.clone(),
+                    ticks,
+                );
+            }
+        }
+
+        if !blueprint.in_cycle() {
+            if let Some(missing) = blueprint.start_cycle(required, required_manufactured) {
+                return missing;
+            }
+        }
+
+        let energy_required = blueprint.with_climate_load(recipe.energy);
+
+        if !energy_manager.has_energy(energy_required) {
+            energy_manager.add_deficit(energy_required);
+            return StructureStatus::NoEnergy;
+        }
+
+        energy_manager.withdraw(energy_required);
+        blueprint.advance();
+
+        return StructureStatus::Running;
+    }
+
     pub fn collect(
         &mut self,
         objects: IterMut<Position, MapObject>,
         energy_manager: &mut EnergyManager,
+        logistics: &mut LogisticsManager,
     ) {
         self.zero_deficit();
 
-        let filtered = objects.filter(|(_, o)| o.structure.is_some());
+        let mut filtered: Vec<_> = objects.filter(|(_, o)| o.structure.is_some()).collect();
 
-        for (_, object) in filtered {
+        // goods in working storages are part of the stock while the tick runs,
+        // whatever is left is put away again once everything else had its turn.
+        for (position, object) in filtered.iter_mut() {
+            if let Some(Structure::Storage { structure }) = &mut object.structure {
+                if Self::trades_stock(position, structure.blueprint(), logistics) {
+                    self.unload(structure.blueprint_mut());
+                }
+            }
+        }
+
+        let mut storages = Vec::new();
+
+        for (position, object) in filtered {
             // let time_factor: f64 = update_tick.delta() as f64 / 2000.0;
             let structure = object.structure.as_mut().unwrap();
 
+            if !structure.blueprint().is_enabled() {
+                structure
+                    .blueprint_mut()
+                    .set_status(StructureStatus::Disabled);
+                continue;
+            }
+
+            if structure.blueprint().is_damaged() {
+                let blueprint = structure.blueprint_mut();
+                blueprint.tick_repair();
+                blueprint.set_active(false);
+                blueprint.set_status(StructureStatus::Damaged);
+                continue;
+            }
+
+            if structure.blueprint().downtime() > 0 {
+                let blueprint = structure.blueprint_mut();
+                blueprint.tick_downtime();
+                blueprint.set_status(StructureStatus::Retooling);
+                continue;
+            }
+
+            // Without a road to a base, goods have no way in or out.
+            let ticks = match logistics.route(position) {
+                Some(route) => route.ticks,
+                None if structure.trades_goods() => {
+                    structure
+                        .blueprint_mut()
+                        .set_status(StructureStatus::Disconnected);
+                    continue;
+                }
+                None => 0,
+            };
+
             match structure {
+                // Generators have nothing to wait for.
+                Structure::Base { structure } => {
+                    structure
+                        .blueprint_mut()
+                        .set_status(StructureStatus::Running);
+                }
+                Structure::PowerPlant { structure } => {
+                    structure
+                        .blueprint_mut()
+                        .set_status(StructureStatus::Running);
+                }
+                Structure::SolarPanel { structure } => {
+                    let status = if energy_manager.sunlight() > 0.0 {
+                        StructureStatus::Running
+                    } else {
+                        StructureStatus::Idle
+                    };
+                    structure.blueprint_mut().set_status(status);
+                }
                 Structure::Mine { structure } => {
-                    let energy_required = structure.blueprint().energy_in();
-                    let resource = structure.resource();
-                    let manufactured = structure.manufactured();
+                    let energy_required = structure
+                        .blueprint()
+                        .with_climate_load(structure.blueprint().energy_in());
+                    let resource = &structure.resource().clone();
+                    let manufactured = &structure.manufactured().clone();
 
                     if energy_manager.has_energy(energy_required) {
                         // resource mined.
                         energy_manager.withdraw(energy_required);
-
-                        self.deposit_resource(resource, structure.blueprint().resource_out());
-                        self.deposit_manufactured(
-                            manufactured,
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::Running);
+
+                        logistics.ship(
+                            Cargo::Resource(*resource),
+                            structure.blueprint().resource_out(),
+                            Destination::Stock,
+                            ticks,
+                        );
+                        logistics.ship(
+                            Cargo::Manufactured(*manufactured),
                             structure.blueprint().manufactured_out(),
+                            Destination::Stock,
+                            ticks,
                         );
                     } else {
                         // resource not mined due to missing energy.
                         energy_manager.add_deficit(energy_required);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::NoEnergy);
                         self.add_resource_deficit(resource, structure.blueprint().resource_out());
                         self.add_manufactured_deficit(
                             manufactured,
@@ -454,85 +841,2146 @@ impl ResourceManager {
                         .blueprint()
                         .get_component(&ComponentName::RefineryOutputComponent);
 
-                    if let ComponentGroup::RefineryOutput { component } = component {
-                        let energy_required = component.resource_required_sum();
-                        let has_energy = energy_manager.has_energy(energy_required);
+                    let mut recipe = None;
+                    if let Ok(ComponentGroup::RefineryOutput { component }) = component {
+                        recipe = Some((
+                            component.resource_required_total(),
+                            component.resource_required_sum(),
+                            component.manufactured_out.clone(),
+                        ));
+                    }
 
-                        let has_resources = component.resources().all(|(_, required_map)| {
-                            required_map.iter().all(|(resource, required_amount)| {
-                                self.has_resource(resource, required_amount.clone())
-                            })
-                        });
+                    let mut status = StructureStatus::Idle;
+
+                    if let Some((required, energy_required, manufactured_out)) = recipe {
+                        let recipe = Recipe {
+                            resources: required,
+                            manufactured: HashMap::new(),
+                            energy: energy_required,
+                        };
+
+                        status = self.produce(
+                            position,
+                            structure.blueprint_mut(),
+                            &recipe,
+                            energy_manager,
+                            logistics,
+                        );
 
-                        if has_energy && has_resources {
-                            energy_manager.withdraw(energy_required);
+                        let cycles = structure.blueprint_mut().take_output();
 
-                            for (_, required) in component.resources() {
-                                for (resource, required_amount) in required {
-                                    self.withdraw_resource(resource, required_amount.clone());
-                                }
+                        for (manufactured, amount) in manufactured_out.iter() {
+                            if cycles > 0 {
+                                structure
+                                    .blueprint_mut()
+                                    .store_manufactured(manufactured, amount * cycles);
                             }
 
-                            for manufactured in structure.resources() {
-                                self.deposit_manufactured(
-                                    manufactured,
-                                    component.manufactured_out[manufactured],
-                                );
-                            }
-                        } else {
-                            for manufactured in structure.resources() {
+                            if status.has_deficit() {
                                 // if we don't have required resource to produce commodity we add to deficit
-                                self.add_manufactured_deficit(
-                                    manufactured,
-                                    component.manufactured_out[manufactured],
-                                )
+                                self.add_manufactured_deficit(manufactured, *amount);
                             }
                         }
                     }
+
+                    structure.blueprint_mut().set_status(status);
                 }
                 Structure::Factory { structure } => {
                     let component = structure
                         .blueprint()
                         .get_component(&ComponentName::FactoryOutputComponent);
 
-                    if let ComponentGroup::FactoryOutput { component } = component {
-                        let has_energy = energy_manager.has_energy(component.energy_required);
-                        let has_resources =
-                            component
-                                .resources()
-                                .all(|(required_resource, required_amount)| {
-                                    self.has_resource(required_resource, required_amount.clone())
-                                });
+                    let mut recipe = None;
+                    if let Ok(ComponentGroup::FactoryOutput { component }) = component {
+                        recipe = Some((
+                            component.resource_required.clone(),
+                            component.energy_required,
+                            component.commodity_out,
+                        ));
+                    }
+
+                    let mut status = StructureStatus::Idle;
+
+                    if let Some((required, energy_required, commodity_out)) = recipe {
+                        let recipe = Recipe {
+                            resources: required,
+                            manufactured: HashMap::new(),
+                            energy: energy_required,
+                        };
+
+                        status = self.produce(
+                            position,
+                            structure.blueprint_mut(),
+                            &recipe,
+                            energy_manager,
+                            logistics,
+                        );
 
-                        if has_energy && has_resources {
-                            energy_manager.withdraw(component.energy_required);
+                        let cycles = structure.blueprint_mut().take_output();
+                        let commodity = *structure.commodity();
 
-                            for (required_resource, required_amount) in component.resources() {
-                                self.withdraw_resource(required_resource, required_amount.clone());
-                            }
-                            self.deposit_commodity(structure.commodity(), component.commodity_out);
-                        } else {
+                        if cycles > 0 {
+                            structure
+                                .blueprint_mut()
+                                .store_commodity(&commodity, commodity_out * cycles);
+                        }
+
+                        if status.has_deficit() {
                             // if we don't have required resource to produce commodity we add to deficit
-                            self.add_commodity_deficit(
-                                structure.commodity(),
-                                component.commodity_out,
-                            )
+                            self.add_commodity_deficit(&commodity, commodity_out);
                         }
                     }
+
+                    structure.blueprint_mut().set_status(status);
+                }
+                Structure::Radar { structure } => {
+                    let energy_required = structure
+                        .blueprint()
+                        .with_climate_load(structure.blueprint().energy_in());
+
+                    if energy_manager.has_energy(energy_required) {
+                        energy_manager.withdraw(energy_required);
+                        structure.blueprint_mut().set_active(true);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::Running);
+                    } else {
+                        energy_manager.add_deficit(energy_required);
+                        structure.blueprint_mut().set_active(false);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::NoEnergy);
+                    }
                 }
                 Structure::Storage { structure } => {
-                    for (resource, amount) in self.resources_mut() {
-                        if *amount > 0 {
-                            let amount_stored = structure
-                                .blueprint_mut()
-                                .resource_add(resource, amount.clone());
+                    structure
+                        .blueprint_mut()
+                        .set_status(StructureStatus::Running);
+
+                    storages.push(structure);
+                }
+                Structure::Road { structure } => {
+                    structure
+                        .blueprint_mut()
+                        .set_status(StructureStatus::Running);
+                }
+                Structure::Shield { structure } => {
+                    let energy_required = structure
+                        .blueprint()
+                        .with_climate_load(structure.blueprint().energy_in());
+
+                    if energy_manager.has_energy(energy_required) {
+                        energy_manager.withdraw(energy_required);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::Running);
+                    } else {
+                        energy_manager.add_deficit(energy_required);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::NoEnergy);
+                    }
+                }
+                Structure::Scrubber { structure } => {
+                    let energy_required = structure
+                        .blueprint()
+                        .with_climate_load(structure.blueprint().energy_in());
+
+                    if energy_manager.has_energy(energy_required) {
+                        energy_manager.withdraw(energy_required);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::Running);
+                    } else {
+                        energy_manager.add_deficit(energy_required);
+                        structure
+                            .blueprint_mut()
+                            .set_status(StructureStatus::NoEnergy);
+                    }
+                }
+                Structure::Terraformer { structure } => {
+                    // Nothing left in reach that the project could change.
+                    if structure.target().is_none() {
+                        structure.blueprint_mut().set_status(StructureStatus::Idle);
+                        continue;
+                    }
 
-                            amount.sub_assign(amount_stored);
+                    let recipe = Recipe {
+                        resources: structure.project().resources(),
+                        manufactured: structure.project().manufactured(),
+                        energy: structure.blueprint().energy_in(),
+                    };
+
+                    let status = self.produce(
+                        position,
+                        structure.blueprint_mut(),
+                        &recipe,
+                        energy_manager,
+                        logistics,
+                    );
+
+                    match status {
+                        StructureStatus::MissingResource(resource) => {
+                            self.add_resource_deficit(&resource, recipe.resources[&resource]);
+                        }
+                        StructureStatus::MissingManufactured(manufactured) => {
+                            self.add_manufactured_deficit(
+                                &manufactured,
+                                recipe.manufactured[&manufactured],
+                            );
                         }
+                        _ => {}
                     }
+
+                    structure.blueprint_mut().set_status(status);
                 }
-                _ => {}
             }
         }
+
+        for structure in storages {
+            for (resource, amount) in self.resources_mut() {
+                if *amount > 0 {
+                    let amount_stored = structure
+                        .blueprint_mut()
+                        .resource_add(resource, amount.clone());
+
+                    amount.sub_assign(amount_stored);
+                }
+            }
+        }
+    }
+
+    // Only storages that are running and connected share their goods with the colony.
+    fn trades_stock(
+        position: &Position,
+        blueprint: &StructureBlueprint,
+        logistics: &LogisticsManager,
+    ) -> bool {
+        return blueprint.is_enabled()
+            && !blueprint.is_damaged()
+            && blueprint.downtime() == 0
+            && logistics.route(position).is_some();
+    }
+
+    // Moves everything a storage holds back into the colony stock.
+    fn unload(&mut self, blueprint: &mut StructureBlueprint) {
+        let resources: Vec<Resource> = ResourceStorageTrait::resources(blueprint)
+            .into_iter()
+            .copied()
+            .collect();
+
+        for resource in resources {
+            let amount = ResourceStorageTrait::resource(blueprint, &resource);
+            let taken = blueprint.resource_take(&resource, amount);
+            self.deposit_resource(&resource, taken);
+        }
+    }
+}
+
+// Update ticks a survey takes to finish.
+pub const SURVEY_TICKS: u64 = 25;
+
+// Energy a survey draws every tick while it runs.
+pub const SURVEY_ENERGY: u64 = 10;
+
+// Keeps track of running deposit surveys, each advancing one tick at a time while powered.
+pub struct SurveyManager {
+    surveys: HashMap<Position, u64>,
+}
+
+impl SurveyManager {
+    pub fn new() -> SurveyManager {
+        let surveys = HashMap::new();
+        return SurveyManager { surveys };
+    }
+
+    pub fn start(
+        &mut self,
+        position: &Position,
+        controller: &MapController,
+    ) -> Result<(), SurveyError> {
+        let position = position.clone();
+
+        if !controller.tile_at(&position).explored {
+            return Err(SurveyError::Unexplored { position });
+        }
+
+        let deposit = match controller.object_at(&position).and_then(|o| o.deposit) {
+            Some(deposit) => deposit,
+            None => return Err(SurveyError::NoDeposit { position }),
+        };
+
+        if deposit.surveyed {
+            return Err(SurveyError::AlreadySurveyed { position });
+        }
+
+        if self.surveys.contains_key(&position) {
+            return Err(SurveyError::InProgress { position });
+        }
+
+        self.surveys.insert(position, 0);
+        return Ok(());
+    }
+
+    // Progress of the survey at a position in percent, if one is running.
+    pub fn progress(&self, position: &Position) -> Option<u64> {
+        self.surveys
+            .get(position)
+            .map(|ticks| ticks * 100 / SURVEY_TICKS)
+    }
+
+    // Advances every survey that gets its energy, returns the deposits surveyed this tick.
+    pub fn update(
+        &mut self,
+        controller: &mut MapController,
+        energy_manager: &mut EnergyManager,
+    ) -> Vec<(Position, ResourceDeposit)> {
+        let mut completed = Vec::new();
+
+        for (position, ticks) in self.surveys.iter_mut() {
+            if !energy_manager.has_energy(SURVEY_ENERGY) {
+                energy_manager.add_deficit(SURVEY_ENERGY);
+                continue;
+            }
+
+            energy_manager.withdraw(SURVEY_ENERGY);
+            ticks.add_assign(1);
+
+            if *ticks >= SURVEY_TICKS {
+                completed.push(position.clone());
+            }
+        }
+
+        let mut surveyed = Vec::new();
+
+        for position in completed {
+            self.surveys.remove(&position);
+
+            let object = controller.objects_mut().get_mut(&position);
+            if let Some(deposit) = object.and_then(|o| o.deposit.as_mut()) {
+                deposit.surveyed = true;
+                surveyed.push((position, *deposit));
+            }
+        }
+
+        return surveyed;
+    }
+}
+
+// Samples kept per metric, at the default update rate this is about two minutes.
+pub const HISTORY_SAMPLES: usize = 500;
+
+// A single value tracked over time by the statistics manager.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Metric {
+    EnergyOutput,
+    EnergyStored,
+    EnergyDeficit,
+    Resource(Resource),
+    Manufactured(Manufactured),
+    Commodity(Commodity),
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Metric::EnergyOutput => write!(f, "Energy Output"),
+            Metric::EnergyStored => write!(f, "Energy Stored"),
+            Metric::EnergyDeficit => write!(f, "Energy Deficit"),
+            Metric::Resource(resource) => write!(f, "{}", resource),
+            Metric::Manufactured(manufactured) => write!(f, "{}", manufactured),
+            Metric::Commodity(commodity) => write!(f, "{}", commodity),
+        }
+    }
+}
+
+// Rolling history of the colony stock and energy, sampled once every update.
+pub struct StatisticsManager {
+    metrics: Vec<Metric>,
+    times: VecDeque<f64>,
+    history: HashMap<Metric, VecDeque<u64>>,
+}
+
+impl StatisticsManager {
+    pub fn new(resource_manager: &ResourceManager) -> StatisticsManager {
+        let mut metrics = vec![
+            Metric::EnergyOutput,
+            Metric::EnergyStored,
+            Metric::EnergyDeficit,
+        ];
+
+        let resources = resource_manager.resource_types();
+        metrics.extend(
+            resources
+                .iter()
+                .sorted_by_key(|r| r.to_string())
+                .map(|r| Metric::Resource(*r)),
+        );
+
+        let manufactured = resource_manager.manufactured_types();
+        metrics.extend(
+            manufactured
+                .iter()
+                .sorted_by_key(|m| m.to_string())
+                .map(|m| Metric::Manufactured(*m)),
+        );
+
+        let commodities = resource_manager.commodity_types();
+        metrics.extend(
+            commodities
+                .iter()
+                .sorted_by_key(|c| c.to_string())
+                .map(|c| Metric::Commodity(*c)),
+        );
+
+        let times = VecDeque::with_capacity(HISTORY_SAMPLES);
+
+        let mut history = HashMap::new();
+        for metric in metrics.iter() {
+            history.insert(*metric, VecDeque::with_capacity(HISTORY_SAMPLES));
+        }
+
+        return StatisticsManager {
+            metrics,
+            times,
+            history,
+        };
+    }
+
+    pub fn metrics(&self) -> &Vec<Metric> {
+        &self.metrics
+    }
+
+    pub fn record(
+        &mut self,
+        elapsed: Duration,
+        resource_manager: &ResourceManager,
+        energy_manager: &EnergyManager,
+    ) {
+        if self.times.len() == HISTORY_SAMPLES {
+            self.times.pop_front();
+            for samples in self.history.values_mut() {
+                samples.pop_front();
+            }
+        }
+
+        self.times.push_back(elapsed.as_secs_f64());
+
+        self.push(Metric::EnergyOutput, energy_manager.produced());
+        self.push(Metric::EnergyStored, energy_manager.reserve());
+        self.push(Metric::EnergyDeficit, energy_manager.deficit());
+
+        for (resource, amount) in resource_manager.resources() {
+            self.push(Metric::Resource(*resource), *amount);
+        }
+
+        for (manufactured, amount) in resource_manager.manufactured() {
+            self.push(Metric::Manufactured(*manufactured), *amount);
+        }
+
+        for (commodity, amount) in resource_manager.commodities() {
+            self.push(Metric::Commodity(*commodity), *amount);
+        }
+    }
+
+    fn push(&mut self, metric: Metric, value: u64) {
+        if let Some(samples) = self.history.get_mut(&metric) {
+            samples.push_back(value);
+        }
+    }
+
+    // Recorded values of a metric, oldest first.
+    pub fn values(&self, metric: &Metric) -> Vec<u64> {
+        return match self.history.get(metric) {
+            Some(samples) => samples.iter().copied().collect(),
+            None => vec![],
+        };
+    }
+
+    // Recorded values of a metric paired with the game time they were sampled at.
+    pub fn points(&self, metric: &Metric) -> Vec<(f64, f64)> {
+        return self
+            .times
+            .iter()
+            .zip(self.values(metric))
+            .map(|(time, value)| (*time, value as f64))
+            .collect();
+    }
+
+    pub fn latest(&self, metric: &Metric) -> u64 {
+        return self
+            .history
+            .get(metric)
+            .and_then(|samples| samples.back().copied())
+            .unwrap_or(0);
+    }
+
+    // Game time covered by the history, in seconds.
+    pub fn time_bounds(&self) -> [f64; 2] {
+        let first = self.times.front().copied().unwrap_or(0.0);
+        let last = self.times.back().copied().unwrap_or(0.0);
+        return [first, last.max(first + 1.0)];
+    }
+
+    // Change of a metric over the recorded history, scaled to one minute of game time.
+    pub fn income_per_minute(&self, metric: &Metric) -> f64 {
+        let samples = match self.history.get(metric) {
+            Some(samples) if samples.len() > 1 => samples,
+            _ => return 0.0,
+        };
+
+        let duration = self.times.back().unwrap() - self.times.front().unwrap();
+        if duration <= 0.0 {
+            return 0.0;
+        }
+
+        let first = *samples.front().unwrap() as f64;
+        let last = *samples.back().unwrap() as f64;
+
+        return (last - first) / duration * 60.0;
+    }
+}
+
+// Update ticks the bottleneck report looks back over, about one minute.
+pub const BOTTLENECK_SAMPLES: usize = 250;
+
+// Why a structure could not run on a tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Bottleneck {
+    Energy,
+    Resource(Resource),
+    Manufactured(Manufactured),
+    Disconnected,
+    Damaged,
+    OutputFull,
+}
+
+impl Bottleneck {
+    pub fn from_status(status: &StructureStatus) -> Option<Bottleneck> {
+        match status {
+            StructureStatus::NoEnergy => Some(Bottleneck::Energy),
+            StructureStatus::MissingResource(resource) => Some(Bottleneck::Resource(*resource)),
+            StructureStatus::MissingManufactured(manufactured) => {
+                Some(Bottleneck::Manufactured(*manufactured))
+            }
+            StructureStatus::Disconnected => Some(Bottleneck::Disconnected),
+            StructureStatus::Damaged => Some(Bottleneck::Damaged),
+            StructureStatus::OutputFull => Some(Bottleneck::OutputFull),
+            _ => None,
+        }
+    }
+
+    // What the player could build to clear this bottleneck.
+    pub fn suggestion(&self) -> String {
+        match self {
+            Bottleneck::Energy => String::from("build a Power Plant or demolish idle consumers"),
+            Bottleneck::Resource(resource) => {
+                format!("build a Mine on a {} deposit", resource)
+            }
+            Bottleneck::Manufactured(manufactured) => {
+                format!("build a Refinery making {}", manufactured)
+            }
+            Bottleneck::Disconnected => {
+                String::from("build a Road to the Base or park a Hauler next to it")
+            }
+            Bottleneck::Damaged => String::from("send a Construction Rover or build a Shield"),
+            Bottleneck::OutputFull => String::from("build a shorter Road to the Base"),
+        }
+    }
+}
+
+impl Display for Bottleneck {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Bottleneck::Energy => write!(f, "missing energy"),
+            Bottleneck::Resource(resource) => write!(f, "missing {}", resource),
+            Bottleneck::Manufactured(manufactured) => write!(f, "missing {}", manufactured),
+            Bottleneck::Disconnected => write!(f, "not connected"),
+            Bottleneck::Damaged => write!(f, "damaged"),
+            Bottleneck::OutputFull => write!(f, "output full"),
+        }
+    }
+}
+
+// A structure that stalled on a single tick.
+#[derive(Clone, Debug)]
+pub struct Stall {
+    pub position: Position,
+    pub structure: String,
+    pub bottleneck: Bottleneck,
+}
+
+// One ranked line of the report, every structure held back by the same cause.
+pub struct BottleneckEntry {
+    pub bottleneck: Bottleneck,
+    pub ticks: u64,
+    pub structures: Vec<(Position, String)>,
+}
+
+// Remembers which structures stalled and why over the last minute of updates.
+pub struct BottleneckManager {
+    samples: VecDeque<Vec<Stall>>,
+    energy_deficit: VecDeque<u64>,
+}
+
+impl BottleneckManager {
+    pub fn new() -> BottleneckManager {
+        let samples = VecDeque::with_capacity(BOTTLENECK_SAMPLES);
+        let energy_deficit = VecDeque::with_capacity(BOTTLENECK_SAMPLES);
+
+        return BottleneckManager {
+            samples,
+            energy_deficit,
+        };
+    }
+
+    pub fn record(&mut self, objects: Iter<Position, MapObject>, energy_manager: &EnergyManager) {
+        if self.samples.len() == BOTTLENECK_SAMPLES {
+            self.samples.pop_front();
+            self.energy_deficit.pop_front();
+        }
+
+        let stalls = objects
+            .filter_map(|(position, object)| {
+                let structure = object.structure.as_ref()?;
+                let bottleneck = Bottleneck::from_status(&structure.blueprint().status())?;
+
+                Some(Stall {
+                    position: position.clone(),
+                    structure: structure.to_string(),
+                    bottleneck,
+                })
+            })
+            .collect();
+
+        self.samples.push_back(stalls);
+        self.energy_deficit.push_back(energy_manager.deficit());
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    // Average energy missing per tick over the report window.
+    pub fn average_energy_deficit(&self) -> u64 {
+        if self.energy_deficit.is_empty() {
+            return 0;
+        }
+
+        let total: u64 = self.energy_deficit.iter().sum();
+        return total / self.energy_deficit.len() as u64;
+    }
+
+    // Bottlenecks ranked by how many structure ticks they cost, worst first.
+    pub fn report(&self) -> Vec<BottleneckEntry> {
+        let mut entries: HashMap<Bottleneck, BottleneckEntry> = HashMap::new();
+
+        for stall in self.samples.iter().flatten() {
+            let entry = entries
+                .entry(stall.bottleneck)
+                .or_insert_with(|| BottleneckEntry {
+                    bottleneck: stall.bottleneck,
+                    ticks: 0,
+                    structures: vec![],
+                });
+
+            entry.ticks.add_assign(1);
+
+            let known = entry.structures.iter().any(|(p, _)| *p == stall.position);
+            if !known {
+                entry
+                    .structures
+                    .push((stall.position.clone(), stall.structure.clone()));
+            }
+        }
+
+        return entries
+            .into_values()
+            .sorted_by(|a, b| {
+                b.ticks
+                    .cmp(&a.ticks)
+                    .then(a.bottleneck.to_string().cmp(&b.bottleneck.to_string()))
+            })
+            .collect();
+    }
+}
+
+// Road tiles a shipment covers in one update tick.
+pub const ROAD_SPEED: u64 = 2;
+
+// Goods a shipment can carry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cargo {
+    Resource(Resource),
+    Manufactured(Manufactured),
+    Commodity(Commodity),
+}
+
+// Where a shipment unloads, the colony stock at the base or the input buffer of a producer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Destination {
+    Stock,
+    Buffer(Position),
+}
+
+// How goods get between a structure and the nearest base.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Route {
+    // Tiles between the structure and the base.
+    pub tiles: u64,
+    // Update ticks goods spend on the way.
+    pub ticks: u64,
+}
+
+impl Route {
+    // Goods on the road cover ROAD_SPEED tiles every tick.
+    pub fn road(tiles: u64) -> Route {
+        let ticks = tiles.div_ceil(ROAD_SPEED);
+
+        return Route { tiles, ticks };
+    }
+
+    // Goods in a hauler move at the pace of the rover.
+    pub fn hauled(tiles: u64) -> Route {
+        let ticks = tiles * RoverKind::Hauler.ticks_per_step();
+
+        return Route { tiles, ticks };
+    }
+}
+
+pub struct Shipment {
+    pub cargo: Cargo,
+    pub amount: u64,
+    pub destination: Destination,
+    pub ticks: u64,
+    // Producer that sent its output, it waits for the load to arrive before sending more.
+    pub origin: Option<Position>,
+}
+
+// Moves goods between structures and the base over the road network.
+pub struct LogisticsManager {
+    routes: HashMap<Position, Route>,
+    // Ground cost from the bases to every tile a rover or builder can get to.
+    reachable: HashMap<Position, u64>,
+    // Road cost from the bases, kept along with reachable until the map changes.
+    distances: HashMap<Position, u64>,
+    // Map revision and parked haulers the routes were last worked out for.
+    revision: Option<u64>,
+    parked: Vec<Position>,
+    shipments: Vec<Shipment>,
+}
+
+impl LogisticsManager {
+    pub fn new() -> LogisticsManager {
+        let routes = HashMap::new();
+        let reachable = HashMap::new();
+        let distances = HashMap::new();
+        let parked = Vec::new();
+        let shipments = Vec::new();
+
+        return LogisticsManager {
+            routes,
+            reachable,
+            distances,
+            revision: None,
+            parked,
+            shipments,
+        };
+    }
+
+    // Way from a structure anchor to the nearest base, if it is connected at all.
+    pub fn route(&self, anchor: &Position) -> Option<Route> {
+        return self.routes.get(anchor).copied();
+    }
+
+    // Tiles reachable over open ground from a base, as of the last update tick.
+    pub fn reachable(&self) -> &HashMap<Position, u64> {
+        return &self.reachable;
+    }
+
+    // Walks the road network out from every base and records how far each structure is.
+    // Structures off the road network trade through a hauler parked next to them instead.
+    // Nothing is worked out again until the map changes or a hauler moves or parks.
+    pub fn update_routes(&mut self, controller: &MapController, rover_manager: &RoverManager) {
+        let objects = controller.objects();
+        let bases = controller.base_cells();
+
+        let parked: Vec<&Rover> = rover_manager
+            .rovers()
+            .iter()
+            .filter(|r| r.kind() == RoverKind::Hauler && r.status() == RoverStatus::Idle)
+            .collect();
+        let positions: Vec<Position> = parked.iter().map(|r| r.position().clone()).collect();
+
+        let changed = self.revision != Some(controller.revision());
+
+        if !changed && positions == self.parked {
+            return;
+        }
+
+        let ground = Pathfinder::new(controller, Traversal::Ground);
+
+        if changed {
+            self.distances = Pathfinder::new(controller, Traversal::Road).distances(&bases);
+            self.reachable = ground.distances(&bases);
+            self.revision = Some(controller.revision());
+        }
+
+        self.parked = positions;
+
+        // Moves each parked hauler needs to drive over open ground to the closest base tile.
+        let haulers: Vec<(&Position, u64)> = parked
+            .iter()
+            .filter_map(|r| {
+                let moves = bases
+                    .iter()
+                    .filter_map(|base| ground.find(r.position(), base))
+                    .map(|path| path.moves() as u64)
+                    .min()?;
+                return Some((r.position(), moves));
+            })
+            .collect();
+
+        self.routes.clear();
+
+        for (position, object) in objects.list() {
+            let structure = match object.structure.as_ref() {
+                Some(structure) => structure,
+                None => continue,
+            };
+
+            if structure.group() == StructureGroup::Base {
+                self.routes.insert(position.clone(), Route::road(0));
+                continue;
+            }
+
+            // A structure connects through any road or base tile touching its footprint.
+            let cells = structure.group().footprint().cells(position);
+            let closest = Pathfinder::closest(&self.distances, &cells);
+
+            if let Some(distance) = closest {
+                self.routes
+                    .insert(position.clone(), Route::road(distance + 1));
+                continue;
+            }
+
+            // Haulers crawl over open ground, one tile to load up and then the drive home.
+            let hauled = haulers
+                .iter()
+                .filter(|(hauler, _)| cells.iter().any(|cell| cell.neighbours().contains(hauler)))
+                .map(|(_, moves)| *moves)
+                .min();
+
+            if let Some(moves) = hauled {
+                self.routes
+                    .insert(position.clone(), Route::hauled(moves + 1));
+            }
+        }
+    }
+
+    // Sends goods on their way, they arrive after the given number of ticks.
+    pub fn ship(&mut self, cargo: Cargo, amount: u64, destination: Destination, ticks: u64) {
+        self.send(cargo, amount, destination, ticks, None);
+    }
+
+    fn send(
+        &mut self,
+        cargo: Cargo,
+        amount: u64,
+        destination: Destination,
+        ticks: u64,
+        origin: Option<Position>,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        self.shipments.push(Shipment {
+            cargo,
+            amount,
+            destination,
+            ticks: ticks.max(1),
+            origin,
+        });
+    }
+
+    // Whether a load of output sent from a producer is still on its way.
+    pub fn outbound(&self, origin: &Position) -> bool {
+        return self
+            .shipments
+            .iter()
+            .any(|s| s.origin.as_ref() == Some(origin));
+    }
+
+    // Loads the finished goods held at every connected producer onto the road to stock.
+    // A producer sends one load at a time, its output waits while the last load is on the way.
+    pub fn dispatch(&mut self, objects: IterMut<Position, MapObject>) {
+        for (position, object) in objects {
+            let structure = match object.structure.as_mut() {
+                Some(structure) => structure,
+                None => continue,
+            };
+
+            let route = match self.route(position) {
+                Some(route) => route,
+                None => continue,
+            };
+
+            if self.outbound(position) || structure.blueprint().finished() == 0 {
+                continue;
+            }
+
+            let blueprint = structure.blueprint_mut();
+            let mut cargo: Vec<(Cargo, u64)> = Vec::new();
+
+            for (manufactured, amount) in blueprint.take_finished_manufactured() {
+                cargo.push((Cargo::Manufactured(manufactured), amount));
+            }
+
+            for (commodity, amount) in blueprint.take_finished_commodities() {
+                cargo.push((Cargo::Commodity(commodity), amount));
+            }
+
+            for (cargo, amount) in cargo {
+                let origin = Some(position.clone());
+                self.send(cargo, amount, Destination::Stock, route.ticks, origin);
+            }
+        }
+    }
+
+    // Goods of a kind already on their way to a destination.
+    pub fn in_transit(&self, destination: &Destination, cargo: &Cargo) -> u64 {
+        return self
+            .shipments
+            .iter()
+            .filter(|s| s.destination == *destination && s.cargo == *cargo)
+            .map(|s| s.amount)
+            .sum();
+    }
+
+    // Moves every shipment one tick along, and unloads the ones that arrived.
+    pub fn deliver(&mut self, objects: &mut ObjectManager, resource_manager: &mut ResourceManager) {
+        for shipment in self.shipments.iter_mut() {
+            shipment.ticks = shipment.ticks.saturating_sub(1);
+        }
+
+        let (arrived, travelling): (Vec<Shipment>, Vec<Shipment>) =
+            self.shipments.drain(..).partition(|s| s.ticks == 0);
+        self.shipments = travelling;
+
+        for shipment in arrived {
+            if let Destination::Buffer(position) = &shipment.destination {
+                let structure = objects
+                    .get_mut(position)
+                    .and_then(|o| o.structure.as_mut())
+                    .filter(|s| {
+                        s.blueprint()
+                            .has_component(&ComponentName::ProductionComponent)
+                    });
+
+                // goods for a demolished structure go back into stock,
+                // also when something else was built in its place.
+                if let Some(structure) = structure {
+                    match shipment.cargo {
+                        Cargo::Resource(resource) => {
+                            structure
+                                .blueprint_mut()
+                                .buffer_add(&resource, shipment.amount);
+                            continue;
+                        }
+                        Cargo::Manufactured(manufactured) => {
+                            structure
+                                .blueprint_mut()
+                                .buffer_add_manufactured(&manufactured, shipment.amount);
+                            continue;
+                        }
+                        Cargo::Commodity(_) => {}
+                    }
+                }
+            }
+
+            match shipment.cargo {
+                Cargo::Resource(resource) => {
+                    resource_manager.deposit_resource(&resource, shipment.amount);
+                }
+                Cargo::Manufactured(manufactured) => {
+                    resource_manager.deposit_manufactured(&manufactured, shipment.amount);
+                }
+                Cargo::Commodity(commodity) => {
+                    resource_manager.deposit_commodity(&commodity, shipment.amount);
+                }
+            }
+        }
+    }
+}
+
+// Keeps track of every rover, moves them along their paths and feeds them energy.
+pub struct RoverManager {
+    rovers: Vec<Rover>,
+    next_id: u64,
+    selected: Option<u64>,
+}
+
+impl RoverManager {
+    pub fn new() -> RoverManager {
+        let rovers = Vec::new();
+        let next_id = 1;
+        let selected = None;
+
+        return RoverManager {
+            rovers,
+            next_id,
+            selected,
+        };
+    }
+
+    pub fn rovers(&self) -> &Vec<Rover> {
+        &self.rovers
+    }
+
+    pub fn at(&self, position: &Position) -> Option<&Rover> {
+        return self.rovers.iter().find(|r| r.position() == position);
+    }
+
+    pub fn selected(&self) -> Option<&Rover> {
+        let id = self.selected?;
+        return self.rovers.iter().find(|r| r.id() == id);
+    }
+
+    // Rovers on a road or right next to a base run on the grid, everywhere else on battery.
+    fn on_grid(controller: &MapController, position: &Position) -> bool {
+        let mut around = position.neighbours();
+        around.push(position.clone());
+
+        return around.iter().any(|cell| {
+            let group = controller
+                .object_at(cell)
+                .and_then(|o| o.structure.as_ref())
+                .map(|s| s.group());
+
+            match group {
+                Some(StructureGroup::Base) => true,
+                Some(StructureGroup::Road) => cell == position,
+                _ => false,
+            }
+        });
+    }
+
+    // Builds a rover next to the base under the cursor.
+    pub fn spawn(
+        &mut self,
+        kind: RoverKind,
+        controller: &mut MapController,
+        resource_manager: &mut ResourceManager,
+        logistics: &LogisticsManager,
+    ) -> Result<&Rover, RoverError> {
+        let position = controller.position();
+
+        let anchor = match controller.objects().owner(&position) {
+            Some(anchor) => anchor.clone(),
+            None => return Err(RoverError::NoBase { position }),
+        };
+
+        let is_base = matches!(
+            controller
+                .object_at(&anchor)
+                .and_then(|o| o.structure.as_ref()),
+            Some(Structure::Base { .. })
+        );
+
+        if !is_base {
+            return Err(RoverError::NoBase { position });
+        }
+
+        let pathfinder = Pathfinder::new(controller, Traversal::Ground);
+        let cells = StructureGroup::Base.footprint().cells(&anchor);
+
+        let free = cells
+            .iter()
+            .flat_map(|cell| cell.neighbours())
+            .filter(|cell| !cells.contains(cell))
+            .sorted_by_key(|cell| (cell.y, cell.x))
+            .find(|cell| pathfinder.cost(cell).is_some() && self.at(cell).is_none());
+
+        let free = match free {
+            Some(free) => free,
+            None => return Err(RoverError::NoRoom { kind }),
+        };
+
+        let cost = kind.cost();
+        if !resource_manager.has_resources(&cost, controller.objects(), logistics) {
+            return Err(RoverError::InsufficientResources { kind, cost });
+        }
+
+        resource_manager.withdraw_resources(&cost, controller.objects_mut(), logistics);
+
+        let rover = Rover::new(self.next_id, kind, free);
+        self.next_id += 1;
+        self.rovers.push(rover);
+
+        return Ok(self.rovers.last().unwrap());
+    }
+
+    pub fn select(&mut self, position: &Position) -> Result<&Rover, RoverError> {
+        let id = match self.at(position) {
+            Some(rover) => rover.id(),
+            None => {
+                return Err(RoverError::NoRover {
+                    position: position.clone(),
+                })
+            }
+        };
+
+        self.selected = Some(id);
+        return Ok(self.selected().unwrap());
+    }
+
+    // Sends the selected rover to a position, stopping next to it when a structure is there.
+    pub fn order(
+        &mut self,
+        target: &Position,
+        controller: &MapController,
+    ) -> Result<&Rover, RoverError> {
+        let id = self.selected().ok_or(RoverError::NoSelection)?.id();
+        let rover = self.selected().unwrap();
+
+        let pathfinder = Pathfinder::new(controller, Traversal::Ground);
+        let mut path = match pathfinder.find(rover.position(), target) {
+            Some(path) => path,
+            None => {
+                return Err(RoverError::Unreachable {
+                    kind: rover.kind(),
+                    position: target.clone(),
+                })
+            }
+        };
+
+        if path.moves() > 0 && pathfinder.cost(target).is_none() {
+            path.steps.pop();
+        }
+
+        // Rovers never end up parked on top of each other.
+        let end = path.steps.last().unwrap();
+        let parked = self
+            .rovers
+            .iter()
+            .find(|r| r.id() != id && r.position() == end);
+
+        if let Some(parked) = parked {
+            return Err(RoverError::Occupied {
+                kind: parked.kind(),
+                position: end.clone(),
+            });
+        }
+
+        let rover = self.rovers.iter_mut().find(|r| r.id() == id).unwrap();
+        rover.order(path);
+        return Ok(rover);
+    }
+
+    pub fn update(&mut self, controller: &mut MapController, energy_manager: &mut EnergyManager) {
+        let mut sights = Vec::new();
+        let mut parked: Vec<Position> = self.rovers.iter().map(|r| r.position().clone()).collect();
+
+        for (index, rover) in self.rovers.iter_mut().enumerate() {
+            let on_grid = Self::on_grid(controller, rover.position());
+
+            // Only spare output goes into rover batteries.
+            if on_grid && rover.missing_charge() > 0 {
+                let amount = ROVER_CHARGE_RATE.min(rover.missing_charge());
+                rover.charge(energy_manager.withdraw_output(amount));
+            }
+
+            if !rover.tick() {
+                continue;
+            }
+
+            let next = match rover.next_step() {
+                Some(next) => next.clone(),
+                None => continue,
+            };
+
+            // Something may have been built on the path since it was planned.
+            if Pathfinder::new(controller, Traversal::Ground)
+                .cost(&next)
+                .is_none()
+            {
+                rover.stop(RoverStatus::Blocked);
+                continue;
+            }
+
+            // Rovers drive past each other, but do not stop on a tile another one took.
+            let taken = parked
+                .iter()
+                .enumerate()
+                .any(|(other, position)| other != index && *position == next);
+
+            if taken && rover.destination() == Some(&next) {
+                rover.stop(RoverStatus::Blocked);
+                continue;
+            }
+
+            let powered = if on_grid && energy_manager.has_energy(ROVER_MOVE_ENERGY) {
+                energy_manager.withdraw(ROVER_MOVE_ENERGY);
+                true
+            } else {
+                rover.drain(ROVER_MOVE_ENERGY)
+            };
+
+            if !powered {
+                rover.set_status(RoverStatus::NoEnergy);
+                continue;
+            }
+
+            rover.set_status(RoverStatus::Moving);
+            rover.advance();
+            parked[index] = rover.position().clone();
+
+            sights.push((rover.position().clone(), rover.kind()));
+        }
+
+        // Survey rovers also chart the deposits they drive past.
+        for (position, kind) in sights {
+            controller.reveal(&position, kind.sight(), kind == RoverKind::Survey);
+        }
+    }
+}
+
+// Tiles around a construction rover where damaged structures get repaired faster.
+pub const ROVER_REPAIR_RANGE: f64 = 2.0;
+
+// Rolls random hazards every update, warns about them and applies them when they hit.
+pub struct HazardManager {
+    hazards: Vec<Hazard>,
+    // Anchors of every shield that ran on the last update.
+    shields: Vec<Position>,
+}
+
+impl HazardManager {
+    pub fn new() -> HazardManager {
+        let hazards = Vec::new();
+        let shields = Vec::new();
+
+        return HazardManager { hazards, shields };
+    }
+
+    pub fn hazards(&self) -> &Vec<Hazard> {
+        &self.hazards
+    }
+
+    pub fn shielded(&self, position: &Position) -> bool {
+        return self
+            .shields
+            .iter()
+            .any(|shield| shield.distance(position) <= SHIELD_RADIUS);
+    }
+
+    // Share of its output a power plant at a position keeps, in percent.
+    pub fn output_percent(&self, position: &Position) -> u64 {
+        let stormy = self.hazards.iter().any(|hazard| {
+            hazard.kind == HazardKind::DustStorm && hazard.is_active() && hazard.covers(position)
+        });
+
+        if stormy && !self.shielded(position) {
+            return DUST_STORM_OUTPUT;
+        }
+
+        return 100;
+    }
+
+    // Announces new hazards and applies the ones that hit, returns the console messages.
+    pub fn update(&mut self, controller: &mut MapController, rovers: &RoverManager) -> Vec<String> {
+        let mut messages = Vec::new();
+
+        let structures: Vec<(Position, StructureGroup, StructureStatus)> = controller
+            .objects()
+            .list()
+            .filter_map(|(position, o)| {
+                o.structure
+                    .as_ref()
+                    .map(|s| (position.clone(), s.group(), s.blueprint().status()))
+            })
+            .collect();
+
+        self.shields = structures
+            .iter()
+            .filter(|(_, group, status)| {
+                *group == StructureGroup::Shield && *status == StructureStatus::Running
+            })
+            .map(|(position, _, _)| position.clone())
+            .collect();
+
+        // The planet stays calm until there is a colony to hit.
+        if structures.is_empty() {
+            return messages;
+        }
+
+        let radar = structures.iter().any(|(_, group, status)| {
+            *group == StructureGroup::Radar && *status == StructureStatus::Running
+        });
+
+        let mut rng = rand::thread_rng();
+
+        for kind in HazardKind::all() {
+            if self.hazards.iter().any(|hazard| hazard.kind == kind) {
+                continue;
+            }
+
+            if rng.gen_range(0..kind.interval()) != 0 {
+                continue;
+            }
+
+            let target = match kind {
+                HazardKind::DustStorm => structures.choose(&mut rng).map(|(p, _, _)| p.clone()),
+                HazardKind::MeteorStrike => structures.choose(&mut rng).map(|(p, _, _)| {
+                    Position::new(p.x + rng.gen_range(-2..=2), p.y + rng.gen_range(-2..=2))
+                }),
+                HazardKind::SolarFlare => Some(controller.spawn()),
+                HazardKind::EquipmentFailure => structures
+                    .iter()
+                    .filter(|(_, group, _)| {
+                        matches!(
+                            group,
+                            StructureGroup::Power
+                                | StructureGroup::Mine
+                                | StructureGroup::Refinery
+                                | StructureGroup::Factory
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .choose(&mut rng)
+                    .map(|(p, _, _)| p.clone()),
+            };
+
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let warning = if radar {
+                kind.warning() * 2
+            } else {
+                kind.warning()
+            };
+            let seconds = warning * UPDATE_RATE_MS / 1000;
+
+            let message = match kind {
+                HazardKind::SolarFlare => {
+                    format!("Warning: {} in {} s, {}", kind, seconds, kind.mitigation())
+                }
+                _ => format!(
+                    "Warning: {} at {} in {} s, {}",
+                    kind,
+                    target,
+                    seconds,
+                    kind.mitigation()
+                ),
+            };
+            messages.push(message);
+
+            self.hazards.push(Hazard::new(kind, target, warning));
+        }
+
+        let mut hits = Vec::new();
+
+        for hazard in self.hazards.iter_mut() {
+            if hazard.warning > 0 {
+                hazard.warning -= 1;
+
+                if hazard.warning == 0 {
+                    hits.push(hazard.clone());
+                }
+                continue;
+            }
+
+            hazard.remaining = hazard.remaining.saturating_sub(1);
+
+            if hazard.remaining == 0 && hazard.kind == HazardKind::DustStorm {
+                messages.push(format!("Dust storm at {} has passed", hazard.position));
+            }
+        }
+
+        self.hazards
+            .retain(|hazard| hazard.warning > 0 || hazard.remaining > 0);
+
+        for hazard in hits {
+            messages.push(self.strike(&hazard, controller));
+        }
+
+        // Construction rovers lend a hand with repairs close by.
+        let crews: Vec<Position> = rovers
+            .rovers()
+            .iter()
+            .filter(|rover| rover.kind() == RoverKind::Construction)
+            .map(|rover| rover.position().clone())
+            .collect();
+
+        for (anchor, object) in controller.objects_mut().list_mut() {
+            if let Some(structure) = object.structure.as_mut() {
+                let cells = structure.group().footprint().cells(anchor);
+
+                let helped = crews.iter().any(|crew| {
+                    cells
+                        .iter()
+                        .any(|cell| crew.distance(cell) <= ROVER_REPAIR_RANGE)
+                });
+
+                if helped {
+                    structure.blueprint_mut().tick_repair();
+                }
+            }
+        }
+
+        return messages;
+    }
+
+    fn strike(&self, hazard: &Hazard, controller: &mut MapController) -> String {
+        let position = &hazard.position;
+
+        match hazard.kind {
+            HazardKind::DustStorm => format!("Dust storm reached {}", position),
+            HazardKind::MeteorStrike => {
+                if self.shielded(position) {
+                    return format!("A Shield deflected the meteor aimed at {}", position);
+                }
+
+                let mut damaged = Vec::new();
+
+                for (anchor, object) in controller.objects_mut().list_mut() {
+                    if let Some(structure) = object.structure.as_mut() {
+                        let cells = structure.group().footprint().cells(anchor);
+
+                        if cells.iter().any(|cell| hazard.covers(cell)) {
+                            structure.blueprint_mut().damage(METEOR_REPAIR_TICKS);
+                            damaged.push(structure.to_string());
+                        }
+                    }
+                }
+
+                if damaged.is_empty() {
+                    return format!("Meteor struck empty ground at {}", position);
+                }
+
+                return format!(
+                    "Meteor struck {}, damaging {}",
+                    position,
+                    damaged.iter().join(", ")
+                );
+            }
+            HazardKind::SolarFlare => {
+                let mut drained = 0;
+
+                for (anchor, object) in controller.objects_mut().list_mut() {
+                    if let Some(Structure::Base { structure }) = object.structure.as_mut() {
+                        if self.shielded(anchor) {
+                            continue;
+                        }
+
+                        let amount = structure.blueprint().stored() * SOLAR_FLARE_DRAIN / 100;
+                        drained += structure.blueprint_mut().discharge(amount);
+                    }
+                }
+
+                format!("Solar flare drained {} energy from the batteries", drained)
+            }
+            HazardKind::EquipmentFailure => {
+                let structure = controller
+                    .objects_mut()
+                    .get_mut(position)
+                    .and_then(|o| o.structure.as_mut());
+
+                match structure {
+                    Some(structure) => {
+                        structure.blueprint_mut().damage(FAILURE_REPAIR_TICKS);
+                        format!("{} at {} broke down", structure, position)
+                    }
+                    None => format!("Equipment failure at {} hit nothing", position),
+                }
+            }
+        }
+    }
+}
+
+// Colonist health below which the console raises an alarm, worst last.
+pub const HEALTH_WARNINGS: [f64; 3] = [75.0, 50.0, 25.0];
+
+// Lets running structures pollute the map and scrubbers clean it, and tracks how
+// the air around the bases wears on colonist health.
+pub struct PollutionManager {
+    // Colonist health in percent.
+    health: f64,
+    // Average pollution over the base tiles on the last update.
+    exposure: f64,
+}
+
+impl PollutionManager {
+    pub fn new() -> PollutionManager {
+        let health = 100.0;
+        let exposure = 0.0;
+
+        return PollutionManager { health, exposure };
+    }
+
+    pub fn health(&self) -> f64 {
+        return self.health;
+    }
+
+    pub fn exposure(&self) -> f64 {
+        return self.exposure;
+    }
+
+    // Runs after the structures updated, so only the ones that ran pollute or clean.
+    // Returns the console messages.
+    pub fn update(&mut self, controller: &mut MapController) -> Vec<String> {
+        let mut messages = Vec::new();
+        let mut emissions = Vec::new();
+        let mut scrubbers = Vec::new();
+
+        for (anchor, object) in controller.objects().list() {
+            let structure = match object.structure.as_ref() {
+                Some(structure) => structure,
+                None => continue,
+            };
+
+            if structure.blueprint().status() != StructureStatus::Running {
+                continue;
+            }
+
+            if structure.group() == StructureGroup::Scrubber {
+                scrubbers.push(anchor.clone());
+            }
+
+            let cells = structure.group().footprint().cells(anchor);
+            let emission = structure.emission() / cells.len() as f64;
+
+            if emission > 0.0 {
+                for cell in cells {
+                    emissions.push((cell, emission));
+                }
+            }
+        }
+
+        for (cell, emission) in emissions {
+            controller.pollute(&cell, emission);
+        }
+
+        for scrubber in scrubbers {
+            controller.scrub(&scrubber, SCRUBBER_RADIUS, SCRUBBER_RATE);
+        }
+
+        let degraded = controller.spread_pollution();
+        if degraded > 0 {
+            messages.push(format!(
+                "Pollution killed the grass on {} tile(s), it is dirt now",
+                degraded
+            ));
+        }
+
+        let bases = controller.base_cells();
+        if bases.is_empty() {
+            return messages;
+        }
+
+        self.exposure = bases
+            .iter()
+            .map(|cell| controller.tile_at(cell).pollution)
+            .sum::<f64>()
+            / bases.len() as f64;
+
+        let before = self.health;
+
+        if self.exposure > HEALTHY_POLLUTION {
+            let loss = (self.exposure - HEALTHY_POLLUTION) * HEALTH_LOSS;
+            self.health = (self.health - loss).max(0.0);
+        } else {
+            self.health = (self.health + HEALTH_RECOVERY).min(100.0);
+        }
+
+        for warning in HEALTH_WARNINGS {
+            if before >= warning && self.health < warning {
+                messages.push(format!(
+                    "Colonist health fell below {}%, build Scrubbers near the base",
+                    warning
+                ));
+            }
+        }
+
+        return messages;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Flora, PlanetType};
+    use crate::structures::{
+        Base, Factory, Road, Storage, Terraformer, MAX_BUILD_SLOPE, OUTPUT_BUFFER_CAPACITY,
+    };
+    use crate::terraforming::TerraformProject;
+    use worldgen::world::Size;
+
+    fn resource_manager() -> ResourceManager {
+        return ResourceManager::new(
+            vec![
+                Resource::Iron,
+                Resource::Aluminum,
+                Resource::Silica,
+                Resource::Carbon,
+            ],
+            vec![Manufactured::Oxygen],
+            vec![Commodity::Glass],
+        );
+    }
+
+    #[test]
+    fn refund_is_half_the_construction_cost() {
+        let cost = StructureCostFactory::cost_for_structure(&StructureGroup::Power);
+        let refund = StructureCostFactory::refund_for_cost(&cost);
+
+        assert_eq!(refund.get(&Resource::Iron), Some(&25));
+        assert_eq!(refund.get(&Resource::Aluminum), Some(&12));
+        assert_eq!(refund.len(), 2);
+    }
+
+    #[test]
+    fn free_structures_refund_nothing() {
+        let cost = StructureCostFactory::cost_for_structure(&StructureGroup::Mine);
+        assert!(StructureCostFactory::refund_for_cost(&cost).is_empty());
+    }
+
+    #[test]
+    fn salvage_refunds_what_was_paid_for_outposts_on_slopes() {
+        let paid = StructureCostFactory::with_slope(
+            StructureCostFactory::cost_for_outpost(),
+            MAX_BUILD_SLOPE,
+        );
+        let mut base = Base::new();
+        base.blueprint_mut().set_cost(paid);
+        let structure = Structure::Base { structure: base };
+
+        let mut resource_manager = resource_manager();
+        let salvaged = resource_manager.salvage(&structure);
+
+        // Half of the doubled outpost cost.
+        assert_eq!(salvaged.get(&Resource::Iron), Some(&150));
+        assert_eq!(salvaged.get(&Resource::Aluminum), Some(&100));
+        assert_eq!(salvaged.get(&Resource::Silica), Some(&50));
+        assert_eq!(resource_manager.resource(&Resource::Iron), 150);
+    }
+
+    #[test]
+    fn salvage_returns_stored_goods() {
+        let mut storage = Storage::new(vec![Resource::Iron], vec![Commodity::Glass]);
+        ResourceStorageTrait::resource_add(storage.blueprint_mut(), &Resource::Iron, 100);
+        CommodityStorageTrait::commodity_add(storage.blueprint_mut(), &Commodity::Glass, 7);
+        let cost = StructureCostFactory::cost_for_structure(&StructureGroup::Storage);
+        storage.blueprint_mut().set_cost(cost);
+        let structure = Structure::Storage { structure: storage };
+
+        let mut resource_manager = resource_manager();
+        let salvaged = resource_manager.salvage(&structure);
+
+        assert_eq!(salvaged.get(&Resource::Iron), Some(&115));
+        assert_eq!(salvaged.get(&Resource::Aluminum), Some(&5));
+        let iron = resource_manager
+            .resources()
+            .find(|(r, _)| **r == Resource::Iron);
+        assert_eq!(iron, Some((&Resource::Iron, &115)));
+
+        let glass = resource_manager
+            .commodities()
+            .find(|(c, _)| **c == Commodity::Glass);
+        assert_eq!(glass, Some((&Commodity::Glass, &7)));
+    }
+
+    #[test]
+    fn salvage_returns_inputs_of_a_running_cycle() {
+        let mut factory = Factory::new(Commodity::Glass);
+        let blueprint = factory.blueprint_mut();
+        blueprint.set_cost(StructureCostFactory::cost_for_structure(
+            &StructureGroup::Factory,
+        ));
+        blueprint.buffer_add(&Resource::Silica, 30);
+
+        let mut required = HashMap::new();
+        required.insert(Resource::Silica, 20);
+        assert_eq!(blueprint.start_cycle(&required, &HashMap::new()), None);
+
+        let structure = Structure::Factory { structure: factory };
+        let mut resource_manager = resource_manager();
+        resource_manager.salvage(&structure);
+
+        // 10 refunded, 10 left in the buffer and 20 taken by the cycle.
+        assert_eq!(resource_manager.resource(&Resource::Silica), 40);
+        assert_eq!(resource_manager.resource(&Resource::Iron), 30);
+    }
+
+    #[test]
+    fn metrics_list_energy_first_then_stock_by_name() {
+        let statistics_manager = StatisticsManager::new(&resource_manager());
+
+        assert_eq!(
+            statistics_manager.metrics(),
+            &vec![
+                Metric::EnergyOutput,
+                Metric::EnergyStored,
+                Metric::EnergyDeficit,
+                Metric::Resource(Resource::Aluminum),
+                Metric::Resource(Resource::Carbon),
+                Metric::Resource(Resource::Iron),
+                Metric::Resource(Resource::Silica),
+                Metric::Manufactured(Manufactured::Oxygen),
+                Metric::Commodity(Commodity::Glass),
+            ]
+        );
+    }
+
+    #[test]
+    fn energy_is_sampled_before_consumption() {
+        let mut statistics_manager = StatisticsManager::new(&resource_manager());
+        let mut energy_manager = EnergyManager::new();
+        energy_manager.produced = 40;
+        energy_manager.reserve = 300;
+        energy_manager.output = 5;
+        energy_manager.deficit = 10;
+
+        statistics_manager.record(Duration::from_secs(1), &resource_manager(), &energy_manager);
+
+        assert_eq!(statistics_manager.latest(&Metric::EnergyOutput), 40);
+        assert_eq!(statistics_manager.latest(&Metric::EnergyStored), 300);
+        assert_eq!(statistics_manager.latest(&Metric::EnergyDeficit), 10);
+    }
+
+    #[test]
+    fn history_drops_the_oldest_samples() {
+        let mut resource_manager = resource_manager();
+        let mut statistics_manager = StatisticsManager::new(&resource_manager);
+        let energy_manager = EnergyManager::new();
+
+        for second in 0..HISTORY_SAMPLES as u64 + 10 {
+            resource_manager.deposit_resource(&Resource::Iron, 1);
+            statistics_manager.record(
+                Duration::from_secs(second),
+                &resource_manager,
+                &energy_manager,
+            );
+        }
+
+        let iron = statistics_manager.values(&Metric::Resource(Resource::Iron));
+        assert_eq!(iron.len(), HISTORY_SAMPLES);
+        assert_eq!(iron.first(), Some(&11));
+        assert_eq!(statistics_manager.time_bounds(), [10.0, 509.0]);
+    }
+
+    #[test]
+    fn income_is_scaled_to_a_minute() {
+        let mut resource_manager = resource_manager();
+        let mut statistics_manager = StatisticsManager::new(&resource_manager);
+        let energy_manager = EnergyManager::new();
+        let iron = Metric::Resource(Resource::Iron);
+
+        statistics_manager.record(Duration::from_secs(0), &resource_manager, &energy_manager);
+        assert_eq!(statistics_manager.income_per_minute(&iron), 0.0);
+
+        resource_manager.deposit_resource(&Resource::Iron, 50);
+        statistics_manager.record(Duration::from_secs(30), &resource_manager, &energy_manager);
+
+        assert_eq!(statistics_manager.income_per_minute(&iron), 100.0);
+        assert_eq!(
+            statistics_manager.income_per_minute(&Metric::Resource(Resource::Carbon)),
+            0.0
+        );
+    }
+
+    fn stalled(status: StructureStatus) -> MapObject {
+        let mut structure = Structure::Factory {
+            structure: Factory::new(Commodity::Glass),
+        };
+        structure.blueprint_mut().set_status(status);
+
+        return MapObject {
+            structure: Some(structure),
+            deposit: None,
+        };
+    }
+
+    #[test]
+    fn report_ranks_bottlenecks_by_stalled_ticks() {
+        let mut objects = HashMap::new();
+        objects.insert(Position::new(0, 0), stalled(StructureStatus::NoEnergy));
+        objects.insert(
+            Position::new(5, 0),
+            stalled(StructureStatus::MissingResource(Resource::Silica)),
+        );
+        objects.insert(Position::new(9, 0), stalled(StructureStatus::NoEnergy));
+        objects.insert(Position::new(9, 9), stalled(StructureStatus::Running));
+
+        let mut bottleneck_manager = BottleneckManager::new();
+        let energy_manager = EnergyManager::new();
+        for _ in 0..3 {
+            bottleneck_manager.record(objects.iter(), &energy_manager);
+        }
+
+        let report = bottleneck_manager.report();
+        assert_eq!(report.len(), 2);
+
+        assert_eq!(report[0].bottleneck, Bottleneck::Energy);
+        assert_eq!(report[0].ticks, 6);
+        assert_eq!(report[0].structures.len(), 2);
+
+        assert_eq!(report[1].bottleneck, Bottleneck::Resource(Resource::Silica));
+        assert_eq!(report[1].ticks, 3);
+        assert_eq!(
+            report[1].structures,
+            vec![(Position::new(5, 0), String::from("Factory"))]
+        );
+    }
+
+    #[test]
+    fn report_forgets_stalls_outside_the_window() {
+        let mut objects = HashMap::new();
+        objects.insert(Position::new(0, 0), stalled(StructureStatus::NoEnergy));
+
+        let mut bottleneck_manager = BottleneckManager::new();
+        let mut energy_manager = EnergyManager::new();
+        energy_manager.deficit = 500;
+        bottleneck_manager.record(objects.iter(), &energy_manager);
+
+        objects.clear();
+        energy_manager.deficit = 0;
+        for _ in 0..BOTTLENECK_SAMPLES - 1 {
+            bottleneck_manager.record(objects.iter(), &energy_manager);
+        }
+
+        assert_eq!(bottleneck_manager.report().len(), 1);
+        assert_eq!(bottleneck_manager.average_energy_deficit(), 2);
+
+        bottleneck_manager.record(objects.iter(), &energy_manager);
+
+        assert!(bottleneck_manager.report().is_empty());
+        assert_eq!(bottleneck_manager.average_energy_deficit(), 0);
+        assert_eq!(bottleneck_manager.samples(), BOTTLENECK_SAMPLES);
+    }
+
+    // A small map of plain dirt with a base in the west corner.
+    fn colony() -> MapController {
+        let mut controller = MapController::new(Size::of(10, 5), PlanetType::Temperate);
+
+        for y in 0..5 {
+            for x in 0..10 {
+                let tile = controller.tile_at_mut(Position::new(x, y));
+                tile.flora = Flora::Dirt;
+                tile.is_resource = false;
+            }
+        }
+
+        controller.jump(&Position::new(0, 1));
+        controller
+            .add_structure(Structure::Base {
+                structure: Base::new(),
+            })
+            .unwrap();
+
+        return controller;
+    }
+
+    #[test]
+    fn goods_put_away_in_storage_still_pay_for_construction() {
+        let mut controller = colony();
+        let anchor = Position::new(3, 1);
+        controller.jump(&anchor);
+        controller
+            .add_structure(Structure::Storage {
+                structure: Storage::new(vec![Resource::Iron, Resource::Aluminum], vec![]),
+            })
+            .unwrap();
+
+        let mut resource_manager = resource_manager();
+        resource_manager.deposit_resource(&Resource::Iron, 100);
+        resource_manager.deposit_resource(&Resource::Aluminum, 100);
+
+        let mut energy_manager = EnergyManager::new();
+        let mut logistics_manager = LogisticsManager::new();
+        let mut rover_manager = RoverManager::new();
+        logistics_manager.update_routes(&controller, &rover_manager);
+        resource_manager.collect(
+            controller.objects_mut().list_mut(),
+            &mut energy_manager,
+            &mut logistics_manager,
+        );
+        assert_eq!(resource_manager.resource(&Resource::Iron), 0);
+
+        let cost = StructureCostFactory::cost_for_structure(&StructureGroup::Power);
+        let objects = controller.objects();
+        assert!(resource_manager.has_resources(&cost, objects, &logistics_manager));
+        resource_manager.withdraw_resources(&cost, controller.objects_mut(), &logistics_manager);
+
+        controller.jump(&Position::new(0, 1));
+        rover_manager
+            .spawn(
+                RoverKind::Hauler,
+                &mut controller,
+                &mut resource_manager,
+                &logistics_manager,
+            )
+            .unwrap();
+
+        let storage = controller.object_at(&anchor).unwrap().structure.as_ref();
+        let blueprint = storage.unwrap().blueprint();
+        assert_eq!(
+            ResourceStorageTrait::resource(blueprint, &Resource::Iron),
+            20
+        );
+        assert_eq!(
+            ResourceStorageTrait::resource(blueprint, &Resource::Aluminum),
+            55
+        );
+        let objects = controller.objects();
+        assert!(!resource_manager.has_resources(&cost, objects, &logistics_manager));
+    }
+
+    #[test]
+    fn storages_off_the_road_do_not_pay_for_construction() {
+        let mut controller = colony();
+        let anchor = Position::new(7, 2);
+        controller.jump(&anchor);
+        controller
+            .add_structure(Structure::Storage {
+                structure: Storage::new(vec![Resource::Iron, Resource::Aluminum], vec![]),
+            })
+            .unwrap();
+
+        let storage = controller.structure_mut().unwrap().blueprint_mut();
+        ResourceStorageTrait::resource_add(storage, &Resource::Iron, 100);
+        ResourceStorageTrait::resource_add(storage, &Resource::Aluminum, 100);
+
+        let mut logistics_manager = LogisticsManager::new();
+        logistics_manager.update_routes(&controller, &RoverManager::new());
+        assert_eq!(logistics_manager.route(&anchor), None);
+
+        let mut resource_manager = resource_manager();
+        let cost = StructureCostFactory::cost_for_structure(&StructureGroup::Power);
+        let objects = controller.objects();
+        assert!(!resource_manager.has_resources(&cost, objects, &logistics_manager));
+
+        resource_manager.deposit_resource(&Resource::Iron, 100);
+        resource_manager.deposit_resource(&Resource::Aluminum, 100);
+        resource_manager.withdraw_resources(&cost, controller.objects_mut(), &logistics_manager);
+
+        let storage = controller.object_at(&anchor).unwrap().structure.as_ref();
+        let blueprint = storage.unwrap().blueprint();
+        assert_eq!(
+            ResourceStorageTrait::resource(blueprint, &Resource::Iron),
+            100
+        );
+        assert!(resource_manager.resource(&Resource::Iron) < 100);
+    }
+
+    #[test]
+    fn parked_haulers_connect_structures_off_the_road() {
+        let mut controller = colony();
+        controller.jump(&Position::new(5, 0));
+        controller
+            .add_structure(Structure::Factory {
+                structure: Factory::new(Commodity::Glass),
+            })
+            .unwrap();
+
+        let mut logistics_manager = LogisticsManager::new();
+        let mut rover_manager = RoverManager::new();
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert_eq!(logistics_manager.route(&Position::new(5, 0)), None);
+
+        let mut resource_manager = resource_manager();
+        resource_manager.deposit_resource(&Resource::Iron, 100);
+        resource_manager.deposit_resource(&Resource::Aluminum, 100);
+        controller.jump(&Position::new(0, 1));
+        rover_manager
+            .spawn(
+                RoverKind::Hauler,
+                &mut controller,
+                &mut resource_manager,
+                &logistics_manager,
+            )
+            .unwrap();
+
+        rover_manager.select(&Position::new(0, 0)).unwrap();
+        rover_manager
+            .order(&Position::new(4, 0), &controller)
+            .unwrap();
+
+        // Still on its way, the hauler is not carrying anything yet.
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert_eq!(logistics_manager.route(&Position::new(5, 0)), None);
+
+        let mut energy_manager = EnergyManager::new();
+        while rover_manager.selected().unwrap().status() == RoverStatus::Moving {
+            rover_manager.update(&mut controller, &mut energy_manager);
+        }
+
+        // One tile to the hauler and three more to the base, at three ticks a tile.
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert_eq!(
+            logistics_manager.route(&Position::new(5, 0)),
+            Some(Route {
+                tiles: 4,
+                ticks: 12
+            })
+        );
+    }
+
+    #[test]
+    fn routes_follow_roads_placed_and_removed_between_updates() {
+        let mut controller = colony();
+        controller.jump(&Position::new(5, 0));
+        controller
+            .add_structure(Structure::Factory {
+                structure: Factory::new(Commodity::Glass),
+            })
+            .unwrap();
+
+        let mut logistics_manager = LogisticsManager::new();
+        let rover_manager = RoverManager::new();
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert_eq!(logistics_manager.route(&Position::new(5, 0)), None);
+
+        for x in 3..5 {
+            controller.jump(&Position::new(x, 1));
+            controller
+                .add_structure(Structure::Road {
+                    structure: Road::new(),
+                })
+                .unwrap();
+        }
+
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert!(logistics_manager.route(&Position::new(5, 0)).is_some());
+
+        // Nothing changed, the routes worked out last tick still hold.
+        let revision = controller.revision();
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert_eq!(controller.revision(), revision);
+        assert!(logistics_manager.route(&Position::new(5, 0)).is_some());
+
+        controller.jump(&Position::new(3, 1));
+        controller.destroy_structure().unwrap();
+
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert_eq!(logistics_manager.route(&Position::new(5, 0)), None);
+    }
+
+    #[test]
+    fn producers_hold_their_output_until_the_last_load_arrived() {
+        let mut controller = colony();
+        let anchor = Position::new(3, 1);
+        controller.jump(&anchor);
+        controller
+            .add_structure(Structure::Factory {
+                structure: Factory::new(Commodity::Glass),
+            })
+            .unwrap();
+
+        let mut logistics_manager = LogisticsManager::new();
+        let rover_manager = RoverManager::new();
+        logistics_manager.update_routes(&controller, &rover_manager);
+        assert!(logistics_manager.route(&anchor).is_some());
+
+        // A full output buffer keeps the factory from starting another cycle.
+        let blueprint = controller
+            .objects_mut()
+            .get_mut(&anchor)
+            .unwrap()
+            .structure
+            .as_mut();
+        let blueprint = blueprint.unwrap().blueprint_mut();
+        blueprint.store_commodity(&Commodity::Glass, OUTPUT_BUFFER_CAPACITY);
+        let started = blueprint.start_cycle(&HashMap::new(), &HashMap::new());
+        assert_eq!(started, Some(StructureStatus::OutputFull));
+
+        logistics_manager.dispatch(controller.objects_mut().list_mut());
+        assert!(logistics_manager.outbound(&anchor));
+
+        // Goods finished while the load is on the road wait at the factory.
+        let blueprint = controller
+            .objects_mut()
+            .get_mut(&anchor)
+            .unwrap()
+            .structure
+            .as_mut();
+        let blueprint = blueprint.unwrap().blueprint_mut();
+        assert_eq!(blueprint.finished(), 0);
+        blueprint.store_commodity(&Commodity::Glass, 1);
+        logistics_manager.dispatch(controller.objects_mut().list_mut());
+
+        let mut resource_manager = resource_manager();
+        logistics_manager.deliver(controller.objects_mut(), &mut resource_manager);
+        assert!(!logistics_manager.outbound(&anchor));
+        let glass = resource_manager
+            .commodities()
+            .find(|(c, _)| **c == Commodity::Glass);
+        assert_eq!(glass, Some((&Commodity::Glass, &OUTPUT_BUFFER_CAPACITY)));
+
+        let blueprint = controller.object_at(&anchor).unwrap().structure.as_ref();
+        assert_eq!(blueprint.unwrap().blueprint().finished(), 1);
+    }
+
+    #[test]
+    fn terraformers_get_oxygen_by_road_and_return_it_on_salvage() {
+        let mut controller = colony();
+        let anchor = Position::new(5, 0);
+        controller.jump(&anchor);
+        controller
+            .add_structure(Structure::Terraformer {
+                structure: Terraformer::new(TerraformProject::Greening),
+            })
+            .unwrap();
+
+        let mut resource_manager = resource_manager();
+        let mut logistics_manager = LogisticsManager::new();
+        logistics_manager.ship(
+            Cargo::Manufactured(Manufactured::Oxygen),
+            8,
+            Destination::Buffer(anchor.clone()),
+            1,
+        );
+        logistics_manager.deliver(controller.objects_mut(), &mut resource_manager);
+
+        let blueprint = controller.object_at(&anchor).unwrap().structure.as_ref();
+        let blueprint = blueprint.unwrap().blueprint();
+        assert_eq!(blueprint.buffered_manufactured(&Manufactured::Oxygen), 8);
+        assert!(!resource_manager.has_manufactured(&Manufactured::Oxygen, 0));
+
+        let structure = controller.destroy_structure().unwrap();
+        resource_manager.salvage(&structure);
+
+        let oxygen = resource_manager
+            .manufactured()
+            .find(|(m, _)| **m == Manufactured::Oxygen);
+        assert_eq!(oxygen, Some((&Manufactured::Oxygen, &8)));
+    }
+
+    #[test]
+    fn goods_for_a_replaced_producer_go_back_into_stock() {
+        let mut controller = colony();
+        let anchor = Position::new(5, 0);
+        controller.jump(&anchor);
+        controller
+            .add_structure(Structure::Storage {
+                structure: Storage::new(vec![Resource::Iron], vec![]),
+            })
+            .unwrap();
+
+        // Shipped to a factory that was torn down for the storage while the load was on its way.
+        let mut resource_manager = resource_manager();
+        let mut logistics_manager = LogisticsManager::new();
+        logistics_manager.ship(
+            Cargo::Resource(Resource::Iron),
+            8,
+            Destination::Buffer(anchor.clone()),
+            1,
+        );
+        logistics_manager.deliver(controller.objects_mut(), &mut resource_manager);
+
+        assert_eq!(resource_manager.resource(&Resource::Iron), 8);
     }
 }
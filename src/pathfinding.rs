@@ -0,0 +1,377 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::game::{Flora, MapController, Position};
+use crate::structures::{StructureGroup, StructureGroupTrait};
+
+// Cost of moving onto a road or bridge tile, the cheapest step there is.
+pub const ROAD_COST: u64 = 1;
+
+// What a path is allowed to cross.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Traversal {
+    // Open ground, roads and bridges, going around every other structure.
+    Ground,
+    // Roads, bridges and bases only, goods never leave the road network.
+    Road,
+}
+
+// Cost of moving onto open ground, None when the ground cannot be crossed.
+pub fn flora_cost(flora: &Flora) -> Option<u64> {
+    return match flora {
+        Flora::Grass | Flora::Dirt | Flora::SaltFlat => Some(2),
+        Flora::Sand | Flora::Crater => Some(3),
+        Flora::Rock | Flora::Ice => Some(4),
+        Flora::ToxicSwamp => Some(6),
+        // Water needs a bridge, lava cannot be crossed at all.
+        Flora::Water | Flora::Lava => None,
+    };
+}
+
+#[derive(Clone, Debug)]
+pub struct Path {
+    // Every tile along the way, from the start to the goal.
+    pub steps: Vec<Position>,
+}
+
+impl Path {
+    // Number of moves it takes to walk the path.
+    pub fn moves(&self) -> usize {
+        return self.steps.len().saturating_sub(1);
+    }
+}
+
+// A tile waiting in the open set, the heap pops the cheapest estimate first.
+#[derive(Eq, PartialEq)]
+struct Node {
+    estimate: u64,
+    position: Position,
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return Reverse(self.estimate)
+            .cmp(&Reverse(other.estimate))
+            .then_with(|| {
+                (self.position.x, self.position.y).cmp(&(other.position.x, other.position.y))
+            });
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+// Finds routes across the map tiles, taking flora and structures into account.
+pub struct Pathfinder<'a> {
+    controller: &'a MapController,
+    traversal: Traversal,
+}
+
+impl<'a> Pathfinder<'a> {
+    pub fn new(controller: &'a MapController, traversal: Traversal) -> Pathfinder<'a> {
+        return Pathfinder {
+            controller,
+            traversal,
+        };
+    }
+
+    // Cost of moving onto a tile, None when it is blocked.
+    pub fn cost(&self, position: &Position) -> Option<u64> {
+        if !self.controller.map().contains(position) {
+            return None;
+        }
+
+        let group = self
+            .controller
+            .object_at(position)
+            .and_then(|o| o.structure.as_ref())
+            .map(|s| s.group());
+
+        return match (self.traversal, group) {
+            // A road built over water is a bridge.
+            (_, Some(StructureGroup::Road)) => Some(ROAD_COST),
+            (Traversal::Road, Some(StructureGroup::Base)) => Some(ROAD_COST),
+            (Traversal::Road, _) => None,
+            (Traversal::Ground, Some(_)) => None,
+            (Traversal::Ground, None) => flora_cost(&self.controller.tile_at(position).flora),
+        };
+    }
+
+    // Cheapest path between two tiles using A*.
+    // The goal may hold a structure, paths end at it rather than going around.
+    pub fn find(&self, start: &Position, goal: &Position) -> Option<Path> {
+        if !self.controller.map().contains(start) || !self.controller.map().contains(goal) {
+            return None;
+        }
+
+        let heuristic = |position: &Position| -> u64 {
+            let dx = (position.x - goal.x).unsigned_abs() as u64;
+            let dy = (position.y - goal.y).unsigned_abs() as u64;
+            return (dx + dy) * ROAD_COST;
+        };
+
+        let mut costs: HashMap<Position, u64> = HashMap::new();
+        let mut previous: HashMap<Position, Position> = HashMap::new();
+        let mut open = BinaryHeap::new();
+
+        costs.insert(start.clone(), 0);
+        open.push(Node {
+            estimate: heuristic(start),
+            position: start.clone(),
+        });
+
+        while let Some(Node { position, .. }) = open.pop() {
+            if &position == goal {
+                let mut steps = vec![position.clone()];
+                let mut current = &position;
+
+                while let Some(step) = previous.get(current) {
+                    steps.push(step.clone());
+                    current = step;
+                }
+
+                steps.reverse();
+
+                return Some(Path { steps });
+            }
+
+            let cost = costs[&position];
+
+            for neighbour in position.neighbours() {
+                let step = match self.cost(&neighbour) {
+                    Some(step) => step,
+                    None if &neighbour == goal && self.controller.object_at(goal).is_some() => {
+                        ROAD_COST
+                    }
+                    None => continue,
+                };
+
+                let next = cost + step;
+
+                if costs.get(&neighbour).is_none_or(|known| next < *known) {
+                    costs.insert(neighbour.clone(), next);
+                    previous.insert(neighbour.clone(), position.clone());
+                    open.push(Node {
+                        estimate: next + heuristic(&neighbour),
+                        position: neighbour,
+                    });
+                }
+            }
+        }
+
+        return None;
+    }
+
+    // Cheapest cost from the nearest source to every tile that can be reached,
+    // sources themselves cost nothing.
+    pub fn distances(&self, sources: &[Position]) -> HashMap<Position, u64> {
+        let mut costs: HashMap<Position, u64> = HashMap::new();
+        let mut open = BinaryHeap::new();
+
+        for source in sources {
+            costs.insert(source.clone(), 0);
+            open.push(Node {
+                estimate: 0,
+                position: source.clone(),
+            });
+        }
+
+        while let Some(Node { estimate, position }) = open.pop() {
+            if estimate > costs[&position] {
+                continue;
+            }
+
+            for neighbour in position.neighbours() {
+                let step = match self.cost(&neighbour) {
+                    Some(step) => step,
+                    None => continue,
+                };
+
+                let next = estimate + step;
+
+                if costs.get(&neighbour).is_none_or(|known| next < *known) {
+                    costs.insert(neighbour.clone(), next);
+                    open.push(Node {
+                        estimate: next,
+                        position: neighbour,
+                    });
+                }
+            }
+        }
+
+        return costs;
+    }
+
+    // Lowest cost reached on or right next to any of the cells, None if they are cut off.
+    pub fn closest(reached: &HashMap<Position, u64>, cells: &[Position]) -> Option<u64> {
+        return cells
+            .iter()
+            .flat_map(|cell| {
+                let mut around = cell.neighbours();
+                around.push(cell.clone());
+                around
+            })
+            .filter_map(|cell| reached.get(&cell))
+            .min()
+            .copied();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PlanetType;
+    use crate::structures::{Base, Road, Structure};
+    use worldgen::world::Size;
+
+    const WIDTH: i16 = 8;
+    const HEIGHT: i16 = 5;
+
+    // A small map of plain dirt, with the given tiles flooded.
+    fn flat_map(water: &[(i16, i16)]) -> MapController {
+        let mut controller =
+            MapController::new(Size::of(WIDTH as i64, HEIGHT as i64), PlanetType::Temperate);
+
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let tile = controller.tile_at_mut(Position::new(x, y));
+                tile.flora = Flora::Dirt;
+                tile.is_resource = false;
+            }
+        }
+
+        for (x, y) in water {
+            controller.tile_at_mut(Position::new(*x, *y)).flora = Flora::Water;
+        }
+
+        return controller;
+    }
+
+    // Water across the whole map at x = 3.
+    fn river() -> Vec<(i16, i16)> {
+        return (0..HEIGHT).map(|y| (3, y)).collect();
+    }
+
+    fn build(controller: &mut MapController, structure: Structure, x: i16, y: i16) {
+        controller.jump(&Position::new(x, y));
+        controller.add_structure(structure).unwrap();
+    }
+
+    fn road() -> Structure {
+        return Structure::Road {
+            structure: Road::new(),
+        };
+    }
+
+    #[test]
+    fn water_blocks_ground_paths() {
+        let controller = flat_map(&river());
+        let pathfinder = Pathfinder::new(&controller, Traversal::Ground);
+
+        assert_eq!(pathfinder.cost(&Position::new(3, 2)), None);
+        assert!(pathfinder
+            .find(&Position::new(0, 2), &Position::new(6, 2))
+            .is_none());
+    }
+
+    #[test]
+    fn bridge_crosses_water() {
+        let mut controller = flat_map(&river());
+        build(&mut controller, road(), 3, 2);
+
+        let pathfinder = Pathfinder::new(&controller, Traversal::Ground);
+        let path = pathfinder
+            .find(&Position::new(0, 2), &Position::new(6, 2))
+            .unwrap();
+
+        assert!(path.steps.contains(&Position::new(3, 2)));
+        assert_eq!(path.moves(), 6);
+    }
+
+    #[test]
+    fn roads_are_cheaper_than_open_ground() {
+        let mut controller = flat_map(&[]);
+        let start = Position::new(0, 0);
+        let goal = Position::new(6, 0);
+
+        let open = Pathfinder::new(&controller, Traversal::Ground)
+            .find(&start, &goal)
+            .unwrap();
+        assert_eq!(open.moves(), 6);
+
+        // A road one row down is worth the two extra moves onto it and off it.
+        for x in 0..=6 {
+            build(&mut controller, road(), x, 1);
+        }
+
+        let paved = Pathfinder::new(&controller, Traversal::Ground)
+            .find(&start, &goal)
+            .unwrap();
+        assert_eq!(paved.moves(), 8);
+        assert!((0..=6).all(|x| paved.steps.contains(&Position::new(x, 1))));
+    }
+
+    #[test]
+    fn paths_end_at_a_structure_goal() {
+        let mut controller = flat_map(&[]);
+        build(
+            &mut controller,
+            Structure::Base {
+                structure: Base::new(),
+            },
+            5,
+            1,
+        );
+
+        let pathfinder = Pathfinder::new(&controller, Traversal::Ground);
+        let goal = Position::new(5, 2);
+        let path = pathfinder.find(&Position::new(0, 2), &goal).unwrap();
+
+        assert_eq!(path.steps.last(), Some(&goal));
+        assert_eq!(path.steps[path.steps.len() - 2], Position::new(4, 2));
+
+        // Structures are gone around, never through, so their inner tiles are out of reach.
+        let inside = Position::new(6, 2);
+        assert!(pathfinder.find(&Position::new(0, 2), &inside).is_none());
+        assert!(!pathfinder
+            .find(&Position::new(0, 0), &Position::new(7, 0))
+            .unwrap()
+            .steps
+            .contains(&Position::new(5, 1)));
+    }
+
+    #[test]
+    fn unreachable_targets() {
+        let controller = flat_map(&river());
+        let pathfinder = Pathfinder::new(&controller, Traversal::Ground);
+
+        assert!(pathfinder
+            .find(&Position::new(0, 0), &Position::new(WIDTH + 2, 0))
+            .is_none());
+
+        let reached = pathfinder.distances(&[Position::new(0, 0)]);
+        assert_eq!(reached.get(&Position::new(0, 0)), Some(&0));
+        assert!(!reached.contains_key(&Position::new(6, 0)));
+        assert_eq!(Pathfinder::closest(&reached, &[Position::new(6, 2)]), None);
+        // Being right next to a cell is enough.
+        assert_eq!(
+            Pathfinder::closest(&reached, &[Position::new(2, 0)]),
+            Some(flora_cost(&Flora::Dirt).unwrap())
+        );
+    }
+
+    #[test]
+    fn goods_stay_on_roads() {
+        let mut controller = flat_map(&[]);
+        build(&mut controller, road(), 1, 0);
+
+        let reached =
+            Pathfinder::new(&controller, Traversal::Road).distances(&[Position::new(0, 0)]);
+
+        assert_eq!(reached.get(&Position::new(1, 0)), Some(&ROAD_COST));
+        assert!(!reached.contains_key(&Position::new(0, 1)));
+    }
+}
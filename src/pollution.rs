@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter, Result};
+
+// Highest pollution a tile can hold.
+pub const MAX_POLLUTION: f64 = 100.0;
+
+// Share of its pollution a tile passes on to its neighbours on every update.
+pub const POLLUTION_SPREAD: f64 = 0.2;
+
+// Share of its pollution a tile loses on its own on every update.
+pub const POLLUTION_DECAY: f64 = 0.01;
+
+// Grass dies back to dirt on tiles more polluted than this.
+pub const GRASS_DEGRADE_POLLUTION: f64 = 30.0;
+
+// Colonists stay healthy while the air at the base is cleaner than this.
+pub const HEALTHY_POLLUTION: f64 = 5.0;
+
+// Health colonists lose per update for every point of pollution above the healthy level.
+pub const HEALTH_LOSS: f64 = 0.01;
+
+// Health colonists win back per update while the air at the base is clean.
+pub const HEALTH_RECOVERY: f64 = 0.05;
+
+// Tiles around a running scrubber it cleans, and how much from each per update.
+pub const SCRUBBER_RADIUS: f64 = 4.0;
+pub const SCRUBBER_RATE: f64 = 1.5;
+
+// How bad the air on a tile is, for display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PollutionLevel {
+    Clean,
+    Light,
+    Heavy,
+    Toxic,
+}
+
+impl PollutionLevel {
+    pub fn of(pollution: f64) -> PollutionLevel {
+        if pollution < 1.0 {
+            return PollutionLevel::Clean;
+        }
+
+        if pollution < HEALTHY_POLLUTION {
+            return PollutionLevel::Light;
+        }
+
+        if pollution < GRASS_DEGRADE_POLLUTION {
+            return PollutionLevel::Heavy;
+        }
+
+        return PollutionLevel::Toxic;
+    }
+}
+
+impl Display for PollutionLevel {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            PollutionLevel::Clean => write!(f, "clean"),
+            PollutionLevel::Light => write!(f, "light"),
+            PollutionLevel::Heavy => write!(f, "heavy"),
+            PollutionLevel::Toxic => write!(f, "toxic"),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter, Result};
+
+use crate::game::{Position, Resource};
+use crate::pathfinding::Path;
+
+// Energy a rover spends on every tile it moves.
+pub const ROVER_MOVE_ENERGY: u64 = 5;
+
+// Energy a rover battery takes in per update while it is on the grid.
+pub const ROVER_CHARGE_RATE: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RoverKind {
+    Survey,
+    Construction,
+    Hauler,
+}
+
+impl RoverKind {
+    pub fn next(&self) -> RoverKind {
+        match self {
+            RoverKind::Survey => RoverKind::Construction,
+            RoverKind::Construction => RoverKind::Hauler,
+            RoverKind::Hauler => RoverKind::Survey,
+        }
+    }
+
+    pub fn cost(&self) -> HashMap<Resource, u64> {
+        let mut cost = HashMap::new();
+
+        match self {
+            RoverKind::Survey => {
+                cost.insert(Resource::Iron, 20);
+                cost.insert(Resource::Silica, 10);
+            }
+            RoverKind::Construction => {
+                cost.insert(Resource::Iron, 40);
+                cost.insert(Resource::Aluminum, 10);
+            }
+            RoverKind::Hauler => {
+                cost.insert(Resource::Iron, 30);
+                cost.insert(Resource::Aluminum, 20);
+            }
+        }
+
+        return cost;
+    }
+
+    pub fn battery_capacity(&self) -> u64 {
+        match self {
+            RoverKind::Survey => 200,
+            RoverKind::Construction => 150,
+            RoverKind::Hauler => 300,
+        }
+    }
+
+    // Updates it takes to move a single tile, survey rovers are the quickest.
+    pub fn ticks_per_step(&self) -> u64 {
+        match self {
+            RoverKind::Survey => 1,
+            RoverKind::Construction => 2,
+            RoverKind::Hauler => 3,
+        }
+    }
+
+    // How far the rover reveals the map around itself.
+    pub fn sight(&self) -> u16 {
+        match self {
+            RoverKind::Survey => 4,
+            RoverKind::Construction => 2,
+            RoverKind::Hauler => 1,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            RoverKind::Survey => 's',
+            RoverKind::Construction => 'c',
+            RoverKind::Hauler => 'h',
+        }
+    }
+}
+
+impl Display for RoverKind {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            RoverKind::Survey => write!(f, "Survey Rover"),
+            RoverKind::Construction => write!(f, "Construction Rover"),
+            RoverKind::Hauler => write!(f, "Hauler"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoverStatus {
+    Idle,
+    Moving,
+    NoEnergy,
+    Blocked,
+}
+
+impl Display for RoverStatus {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            RoverStatus::Idle => write!(f, "idle"),
+            RoverStatus::Moving => write!(f, "moving"),
+            RoverStatus::NoEnergy => write!(f, "out of energy"),
+            RoverStatus::Blocked => write!(f, "path blocked"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Rover {
+    id: u64,
+    kind: RoverKind,
+    position: Position,
+    path: VecDeque<Position>,
+    battery: u64,
+    // Updates left until the next tile is reached.
+    cooldown: u64,
+    status: RoverStatus,
+}
+
+impl Rover {
+    // Rovers leave the base fully charged.
+    pub fn new(id: u64, kind: RoverKind, position: Position) -> Rover {
+        let battery = kind.battery_capacity();
+
+        return Rover {
+            id,
+            kind,
+            position,
+            path: VecDeque::new(),
+            battery,
+            cooldown: 0,
+            status: RoverStatus::Idle,
+        };
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn kind(&self) -> RoverKind {
+        self.kind
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    pub fn battery(&self) -> u64 {
+        self.battery
+    }
+
+    pub fn status(&self) -> RoverStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: RoverStatus) {
+        self.status = status;
+    }
+
+    // Where the rover is heading, if anywhere.
+    pub fn destination(&self) -> Option<&Position> {
+        self.path.back()
+    }
+
+    // Counts down to the next move, true once the rover may step again.
+    pub fn tick(&mut self) -> bool {
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return false;
+        }
+
+        return true;
+    }
+
+    pub fn next_step(&self) -> Option<&Position> {
+        self.path.front()
+    }
+
+    // Follows a new path, the first step is where the rover already stands.
+    pub fn order(&mut self, path: Path) {
+        self.path = path.steps.into_iter().skip(1).collect();
+        self.cooldown = 0;
+        self.status = RoverStatus::Moving;
+    }
+
+    pub fn stop(&mut self, status: RoverStatus) {
+        self.path.clear();
+        self.status = status;
+    }
+
+    pub fn advance(&mut self) {
+        if let Some(position) = self.path.pop_front() {
+            self.position = position;
+            self.cooldown = self.kind.ticks_per_step() - 1;
+        }
+
+        if self.path.is_empty() {
+            self.status = RoverStatus::Idle;
+        }
+    }
+
+    // Takes energy from the battery, returns false when it holds too little.
+    pub fn drain(&mut self, amount: u64) -> bool {
+        if self.battery < amount {
+            return false;
+        }
+
+        self.battery -= amount;
+        return true;
+    }
+
+    // Room left in the battery.
+    pub fn missing_charge(&self) -> u64 {
+        self.kind.battery_capacity() - self.battery
+    }
+
+    pub fn charge(&mut self, amount: u64) {
+        self.battery = (self.battery + amount).min(self.kind.battery_capacity());
+    }
+}
@@ -1,24 +1,39 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Result};
 use std::ops::{AddAssign, Sub, SubAssign};
+use std::result::Result as StdResult;
 
 use crate::component::{
     BatteryComponent, CommodityStorageComponent, ComponentGroup, ComponentName, EnergyComponent,
-    FactoryOutputComponent, MineOutputComponent, RefineryOutputComponent, ResourceStorageComponent,
+    FactoryOutputComponent, MineOutputComponent, ProductionComponent, RefineryOutputComponent,
+    ResourceStorageComponent, SightComponent,
+};
+use crate::errors::{ComponentError, PlacementError, RetoolError};
+use crate::game::{
+    Commodity, Flora, Footprint, Manufactured, MapController, MapObject, MapTile, PlanetType,
+    Position, Resource,
 };
-use crate::game::{Commodity, Flora, Manufactured, MapObject, MapTile, Resource};
 use crate::gui::MenuSelector;
-use crate::managers::ResourceManager;
+use crate::managers::{LogisticsManager, ResourceManager, RoverManager};
+use crate::pathfinding::Pathfinder;
+use crate::terraforming::{TerraformProject, TERRAFORM_CYCLE_TICKS};
+use itertools::Itertools;
 use std::slice::Iter;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum StructureGroup {
     Base,
     Power,
+    Solar,
     Mine,
     Refinery,
     Factory,
     Storage,
+    Radar,
+    Road,
+    Shield,
+    Terraformer,
+    Scrubber,
 }
 
 impl Display for StructureGroup {
@@ -27,6 +42,25 @@ impl Display for StructureGroup {
     }
 }
 
+impl StructureGroup {
+    pub fn footprint(&self) -> Footprint {
+        match self {
+            StructureGroup::Base => Footprint::new(3, 3),
+            StructureGroup::Power => Footprint::new(1, 1),
+            StructureGroup::Solar => Footprint::new(2, 1),
+            StructureGroup::Mine => Footprint::new(1, 1),
+            StructureGroup::Refinery => Footprint::new(2, 2),
+            StructureGroup::Factory => Footprint::new(2, 2),
+            StructureGroup::Storage => Footprint::new(2, 2),
+            StructureGroup::Radar => Footprint::new(1, 1),
+            StructureGroup::Road => Footprint::new(1, 1),
+            StructureGroup::Shield => Footprint::new(1, 1),
+            StructureGroup::Terraformer => Footprint::new(2, 2),
+            StructureGroup::Scrubber => Footprint::new(1, 1),
+        }
+    }
+}
+
 pub trait EnergyTrait {
     fn energy_in(&self) -> u64;
     fn energy_out(&self) -> u64;
@@ -40,6 +74,13 @@ pub trait BatteryTrait {
     fn discharge(&mut self, amount: u64) -> u64;
 }
 
+pub trait SightTrait {
+    fn radius(&self) -> u16;
+    fn surveys(&self) -> bool;
+    fn active(&self) -> bool;
+    fn set_active(&mut self, active: bool);
+}
+
 pub trait MineOutputTrait {
     fn resource_out(&self) -> u64;
     fn manufactured_out(&self) -> u64;
@@ -49,6 +90,7 @@ pub trait ResourceStorageTrait {
     fn capacity(&self, group: &Resource) -> u64;
     fn resource(&self, group: &Resource) -> u64;
     fn resource_add(&mut self, group: &Resource, amount: u64) -> u64;
+    fn resource_take(&mut self, group: &Resource, amount: u64) -> u64;
     fn resources(&self) -> Vec<&Resource>;
 }
 
@@ -59,30 +101,161 @@ pub trait CommodityStorageTrait {
     fn commodities(&self) -> Vec<&Commodity>;
 }
 
+pub trait ProductionTrait {
+    fn cycle_ticks(&self) -> u64;
+    fn progress(&self) -> u64;
+    fn in_cycle(&self) -> bool;
+    fn buffered(&self, resource: &Resource) -> u64;
+    fn buffered_resources(&self) -> Vec<Resource>;
+    fn buffer_add(&mut self, resource: &Resource, amount: u64);
+    fn buffer_take(&mut self, resource: &Resource) -> u64;
+    fn buffered_manufactured(&self, manufactured: &Manufactured) -> u64;
+    fn buffered_manufactured_types(&self) -> Vec<Manufactured>;
+    fn buffer_add_manufactured(&mut self, manufactured: &Manufactured, amount: u64);
+    fn buffer_take_manufactured(&mut self, manufactured: &Manufactured) -> u64;
+    fn cycle_inputs(&self) -> HashMap<Resource, u64>;
+    fn cycle_manufactured(&self) -> HashMap<Manufactured, u64>;
+    fn start_cycle(
+        &mut self,
+        required: &HashMap<Resource, u64>,
+        required_manufactured: &HashMap<Manufactured, u64>,
+    ) -> Option<StructureStatus>;
+    fn advance(&mut self) -> bool;
+    fn reset_cycle(&mut self);
+    fn take_output(&mut self) -> u64;
+    fn finished(&self) -> u64;
+    fn finished_manufactured(&self) -> HashMap<Manufactured, u64>;
+    fn finished_commodities(&self) -> HashMap<Commodity, u64>;
+    fn store_manufactured(&mut self, manufactured: &Manufactured, amount: u64);
+    fn store_commodity(&mut self, commodity: &Commodity, amount: u64);
+    fn take_finished_manufactured(&mut self) -> HashMap<Manufactured, u64>;
+    fn take_finished_commodities(&mut self) -> HashMap<Commodity, u64>;
+}
+
 #[derive(Debug)]
 pub enum Structure {
     Base { structure: Base },
     PowerPlant { structure: PowerPlant },
+    SolarPanel { structure: SolarPanel },
     Mine { structure: Mine },
     Refinery { structure: Refinery },
     Factory { structure: Factory },
     Storage { structure: Storage },
+    Radar { structure: Radar },
+    Road { structure: Road },
+    Shield { structure: Shield },
+    Terraformer { structure: Terraformer },
+    Scrubber { structure: Scrubber },
 }
 
 impl Display for Structure {
     fn fmt(&self, f: &mut Formatter) -> Result {
         let name = match self {
             Structure::PowerPlant { .. } => "Power Plant",
+            Structure::SolarPanel { .. } => "Solar Panel",
             Structure::Mine { .. } => "Mine",
             Structure::Base { .. } => "Base",
             Structure::Refinery { .. } => "Refinery",
             Structure::Factory { .. } => "Factory",
             Structure::Storage { .. } => "Storage",
+            Structure::Radar { .. } => "Radar",
+            Structure::Road { .. } => "Road",
+            Structure::Shield { .. } => "Shield",
+            Structure::Terraformer { .. } => "Terraformer",
+            Structure::Scrubber { .. } => "Scrubber",
         };
         write!(f, "{}", name)
     }
 }
 
+impl Structure {
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        match self {
+            Structure::Base { structure } => structure.blueprint(),
+            Structure::PowerPlant { structure } => structure.blueprint(),
+            Structure::SolarPanel { structure } => structure.blueprint(),
+            Structure::Mine { structure } => structure.blueprint(),
+            Structure::Refinery { structure } => structure.blueprint(),
+            Structure::Factory { structure } => structure.blueprint(),
+            Structure::Storage { structure } => structure.blueprint(),
+            Structure::Radar { structure } => structure.blueprint(),
+            Structure::Road { structure } => structure.blueprint(),
+            Structure::Shield { structure } => structure.blueprint(),
+            Structure::Terraformer { structure } => structure.blueprint(),
+            Structure::Scrubber { structure } => structure.blueprint(),
+        }
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        match self {
+            Structure::Base { structure } => structure.blueprint_mut(),
+            Structure::PowerPlant { structure } => structure.blueprint_mut(),
+            Structure::SolarPanel { structure } => structure.blueprint_mut(),
+            Structure::Mine { structure } => structure.blueprint_mut(),
+            Structure::Refinery { structure } => structure.blueprint_mut(),
+            Structure::Factory { structure } => structure.blueprint_mut(),
+            Structure::Storage { structure } => structure.blueprint_mut(),
+            Structure::Radar { structure } => structure.blueprint_mut(),
+            Structure::Road { structure } => structure.blueprint_mut(),
+            Structure::Shield { structure } => structure.blueprint_mut(),
+            Structure::Terraformer { structure } => structure.blueprint_mut(),
+            Structure::Scrubber { structure } => structure.blueprint_mut(),
+        }
+    }
+
+    // Whether the structure moves goods, and so has to be connected to a base.
+    pub fn trades_goods(&self) -> bool {
+        return matches!(
+            self,
+            Structure::Mine { .. }
+                | Structure::Refinery { .. }
+                | Structure::Factory { .. }
+                | Structure::Storage { .. }
+                | Structure::Terraformer { .. }
+        );
+    }
+
+    // Pollution the structure puts out on every tick it runs, spread over its footprint.
+    pub fn emission(&self) -> f64 {
+        match self {
+            Structure::PowerPlant { .. } => 0.5,
+            Structure::Mine { .. } => 1.0,
+            Structure::Refinery { .. } => 1.5,
+            Structure::Factory { .. } => 2.0,
+            _ => 0.0,
+        }
+    }
+
+    // Energy the structure draws on every tick it runs, heating and cooling included.
+    pub fn energy_use(&self) -> u64 {
+        let energy = match self {
+            Structure::Refinery { structure } => {
+                match structure
+                    .blueprint()
+                    .get_component(&ComponentName::RefineryOutputComponent)
+                {
+                    Ok(ComponentGroup::RefineryOutput { component }) => {
+                        component.resource_required_sum()
+                    }
+                    _ => 0,
+                }
+            }
+            Structure::Factory { structure } => {
+                match structure
+                    .blueprint()
+                    .get_component(&ComponentName::FactoryOutputComponent)
+                {
+                    Ok(ComponentGroup::FactoryOutput { component }) => component.energy_required,
+                    _ => 0,
+                }
+            }
+            _ => self.blueprint().energy_in(),
+        };
+
+        return self.blueprint().with_climate_load(energy);
+    }
+}
+
 pub trait StructureGroupTrait {
     fn group(&self) -> StructureGroup;
 }
@@ -92,41 +265,193 @@ impl StructureGroupTrait for Structure {
         match self {
             Structure::Base { .. } => StructureGroup::Base,
             Structure::PowerPlant { .. } => StructureGroup::Power,
+            Structure::SolarPanel { .. } => StructureGroup::Solar,
             Structure::Mine { .. } => StructureGroup::Mine,
             Structure::Factory { .. } => StructureGroup::Factory,
             Structure::Refinery { .. } => StructureGroup::Refinery,
             Structure::Storage { .. } => StructureGroup::Storage,
+            Structure::Radar { .. } => StructureGroup::Radar,
+            Structure::Road { .. } => StructureGroup::Road,
+            Structure::Shield { .. } => StructureGroup::Shield,
+            Structure::Terraformer { .. } => StructureGroup::Terraformer,
+            Structure::Scrubber { .. } => StructureGroup::Scrubber,
+        }
+    }
+}
+
+// What a structure managed to do on the last update tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StructureStatus {
+    Idle,
+    Running,
+    NoEnergy,
+    MissingResource(Resource),
+    MissingManufactured(Manufactured),
+    Disabled,
+    Retooling,
+    Disconnected,
+    Damaged,
+    OutputFull,
+}
+
+impl StructureStatus {
+    pub fn is_powered(&self) -> bool {
+        return *self != StructureStatus::NoEnergy;
+    }
+
+    pub fn has_deficit(&self) -> bool {
+        return matches!(
+            self,
+            StructureStatus::NoEnergy
+                | StructureStatus::MissingResource(_)
+                | StructureStatus::MissingManufactured(_)
+                | StructureStatus::Disconnected
+        );
+    }
+}
+
+impl Display for StructureStatus {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            StructureStatus::Idle => write!(f, "idle"),
+            StructureStatus::Running => write!(f, "running"),
+            StructureStatus::NoEnergy => write!(f, "no energy"),
+            StructureStatus::MissingResource(resource) => write!(f, "missing {}", resource),
+            StructureStatus::MissingManufactured(manufactured) => {
+                write!(f, "missing {}", manufactured)
+            }
+            StructureStatus::Disabled => write!(f, "disabled"),
+            StructureStatus::Retooling => write!(f, "retooling"),
+            StructureStatus::Disconnected => write!(f, "not connected"),
+            StructureStatus::Damaged => write!(f, "damaged"),
+            StructureStatus::OutputFull => write!(f, "output full"),
         }
     }
 }
 
 pub struct StructureBlueprint {
     components: HashMap<ComponentName, ComponentGroup>,
+    status: StructureStatus,
+    enabled: bool,
+    downtime: u64,
+    repair: u64,
+    climate_load: u64,
+    // What was paid to build it, salvage refunds a share of this.
+    cost: HashMap<Resource, u64>,
 }
 
 impl StructureBlueprint {
-    pub fn add_component(&mut self, name: ComponentName, component: ComponentGroup) {
-        self.components.insert(name, component);
+    pub fn new(components: HashMap<ComponentName, ComponentGroup>) -> StructureBlueprint {
+        let status = StructureStatus::Idle;
+        let enabled = true;
+        let downtime = 0;
+        let repair = 0;
+        let climate_load = 0;
+        let cost = HashMap::new();
+
+        return StructureBlueprint {
+            components,
+            status,
+            enabled,
+            downtime,
+            repair,
+            climate_load,
+            cost,
+        };
     }
 
-    pub fn get_component(&self, name: &ComponentName) -> &ComponentGroup {
-        let component = self.components.get(name);
+    pub fn cost(&self) -> &HashMap<Resource, u64> {
+        return &self.cost;
+    }
 
-        if component.is_some() {
-            return component.unwrap();
-        } else {
-            panic!("{} is missing in this structure", name)
-        }
+    pub fn set_cost(&mut self, cost: HashMap<Resource, u64>) {
+        self.cost = cost;
     }
 
-    pub fn get_component_mut(&mut self, name: &ComponentName) -> &mut ComponentGroup {
-        let component = self.components.get_mut(name);
+    // Extra energy the structure spends on heating or cooling, in percent.
+    pub fn climate_load(&self) -> u64 {
+        return self.climate_load;
+    }
 
-        if component.is_some() {
-            return component.unwrap();
-        } else {
-            panic!("{} is missing in this structure", name)
-        }
+    pub fn set_climate_load(&mut self, load: u64) {
+        self.climate_load = load;
+    }
+
+    // Energy draw including what goes into heating or cooling.
+    pub fn with_climate_load(&self, energy: u64) -> u64 {
+        return energy * (100 + self.climate_load) / 100;
+    }
+
+    // Update ticks of repairs left after a hazard hit the structure.
+    pub fn repair(&self) -> u64 {
+        return self.repair;
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        return self.repair > 0;
+    }
+
+    // A new hit never shortens repairs already under way.
+    pub fn damage(&mut self, ticks: u64) {
+        self.repair = self.repair.max(ticks);
+    }
+
+    pub fn tick_repair(&mut self) {
+        self.repair = self.repair.saturating_sub(1);
+    }
+
+    // Update ticks left before the structure can work again.
+    pub fn downtime(&self) -> u64 {
+        return self.downtime;
+    }
+
+    pub fn set_downtime(&mut self, ticks: u64) {
+        self.downtime = ticks;
+    }
+
+    pub fn tick_downtime(&mut self) {
+        self.downtime = self.downtime.saturating_sub(1);
+    }
+
+    // Disabled structures are skipped by the update loop, they neither consume nor produce.
+    pub fn is_enabled(&self) -> bool {
+        return self.enabled;
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn status(&self) -> StructureStatus {
+        return self.status;
+    }
+
+    pub fn set_status(&mut self, status: StructureStatus) {
+        self.status = status;
+    }
+
+    pub fn add_component(&mut self, name: ComponentName, component: ComponentGroup) {
+        self.components.insert(name, component);
+    }
+
+    pub fn get_component(
+        &self,
+        name: &ComponentName,
+    ) -> StdResult<&ComponentGroup, ComponentError> {
+        return self
+            .components
+            .get(name)
+            .ok_or_else(|| ComponentError::Missing(name.clone()));
+    }
+
+    pub fn get_component_mut(
+        &mut self,
+        name: &ComponentName,
+    ) -> StdResult<&mut ComponentGroup, ComponentError> {
+        return self
+            .components
+            .get_mut(name)
+            .ok_or_else(|| ComponentError::Missing(name.clone()));
     }
 
     pub fn has_component(&self, name: &ComponentName) -> bool {
@@ -137,18 +462,18 @@ impl StructureBlueprint {
 impl EnergyTrait for StructureBlueprint {
     fn energy_in(&self) -> u64 {
         match self.get_component(&ComponentName::EnergyComponent) {
-            ComponentGroup::Energy {
+            Ok(ComponentGroup::Energy {
                 component: EnergyComponent { energy_in, .. },
-            } => *energy_in,
+            }) => *energy_in,
             _ => 0,
         }
     }
 
     fn energy_out(&self) -> u64 {
         match self.get_component(&ComponentName::EnergyComponent) {
-            ComponentGroup::Energy {
+            Ok(ComponentGroup::Energy {
                 component: EnergyComponent { energy_out, .. },
-            } => *energy_out,
+            }) => *energy_out,
             _ => 0,
         }
     }
@@ -157,27 +482,27 @@ impl EnergyTrait for StructureBlueprint {
 impl BatteryTrait for StructureBlueprint {
     fn capacity(&self) -> u64 {
         match self.get_component(&ComponentName::BatteryComponent) {
-            ComponentGroup::Battery {
+            Ok(ComponentGroup::Battery {
                 component: BatteryComponent { capacity, .. },
-            } => *capacity,
+            }) => *capacity,
             _ => 0,
         }
     }
 
     fn capacity_free(&self) -> u64 {
         match self.get_component(&ComponentName::BatteryComponent) {
-            ComponentGroup::Battery {
+            Ok(ComponentGroup::Battery {
                 component: BatteryComponent { capacity, stored },
-            } => *capacity - *stored,
+            }) => *capacity - *stored,
             _ => 0,
         }
     }
 
     fn stored(&self) -> u64 {
         match self.get_component(&ComponentName::BatteryComponent) {
-            ComponentGroup::Battery {
+            Ok(ComponentGroup::Battery {
                 component: BatteryComponent { stored, .. },
-            } => *stored,
+            }) => *stored,
             _ => 0,
         }
     }
@@ -186,9 +511,9 @@ impl BatteryTrait for StructureBlueprint {
         let component = self.get_component_mut(&ComponentName::BatteryComponent);
 
         match component {
-            ComponentGroup::Battery {
+            Ok(ComponentGroup::Battery {
                 component: BatteryComponent { capacity, stored },
-            } => {
+            }) => {
                 let free = capacity.sub(*stored);
 
                 if free == 0 {
@@ -211,9 +536,9 @@ impl BatteryTrait for StructureBlueprint {
         let component = self.get_component_mut(&ComponentName::BatteryComponent);
 
         match component {
-            ComponentGroup::Battery {
+            Ok(ComponentGroup::Battery {
                 component: BatteryComponent { stored, .. },
-            } => {
+            }) => {
                 if *stored < amount {
                     let stored_available = *stored;
                     stored.sub_assign(stored_available);
@@ -228,24 +553,353 @@ impl BatteryTrait for StructureBlueprint {
     }
 }
 
+impl SightTrait for StructureBlueprint {
+    fn radius(&self) -> u16 {
+        match self.get_component(&ComponentName::SightComponent) {
+            Ok(ComponentGroup::Sight {
+                component: SightComponent { radius, .. },
+            }) => *radius,
+            _ => 0,
+        }
+    }
+
+    fn surveys(&self) -> bool {
+        match self.get_component(&ComponentName::SightComponent) {
+            Ok(ComponentGroup::Sight {
+                component: SightComponent { survey, .. },
+            }) => *survey,
+            _ => false,
+        }
+    }
+
+    fn active(&self) -> bool {
+        match self.get_component(&ComponentName::SightComponent) {
+            Ok(ComponentGroup::Sight {
+                component: SightComponent { active, .. },
+            }) => *active,
+            _ => false,
+        }
+    }
+
+    fn set_active(&mut self, value: bool) {
+        if let Ok(ComponentGroup::Sight {
+            component: SightComponent { active, .. },
+        }) = self.get_component_mut(&ComponentName::SightComponent)
+        {
+            *active = value;
+        }
+    }
+}
+
+impl ProductionTrait for StructureBlueprint {
+    fn cycle_ticks(&self) -> u64 {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production {
+                component: ProductionComponent { cycle_ticks, .. },
+            }) => *cycle_ticks,
+            _ => 0,
+        }
+    }
+
+    fn progress(&self) -> u64 {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production {
+                component: ProductionComponent { progress, .. },
+            }) => *progress,
+            _ => 0,
+        }
+    }
+
+    fn in_cycle(&self) -> bool {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production {
+                component: ProductionComponent { in_cycle, .. },
+            }) => *in_cycle,
+            _ => false,
+        }
+    }
+
+    fn buffered(&self, resource: &Resource) -> u64 {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component.buffered(resource),
+            _ => 0,
+        }
+    }
+
+    fn buffered_resources(&self) -> Vec<Resource> {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.input.keys().copied().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn buffer_add(&mut self, resource: &Resource, amount: u64) {
+        if let Ok(ComponentGroup::Production { component }) =
+            self.get_component_mut(&ComponentName::ProductionComponent)
+        {
+            component
+                .input
+                .entry(*resource)
+                .or_insert(0)
+                .add_assign(amount);
+        }
+    }
+
+    fn buffer_take(&mut self, resource: &Resource) -> u64 {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.input.remove(resource).unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    fn buffered_manufactured(&self, manufactured: &Manufactured) -> u64 {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.buffered_manufactured(manufactured)
+            }
+            _ => 0,
+        }
+    }
+
+    fn buffered_manufactured_types(&self) -> Vec<Manufactured> {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.manufactured_input.keys().copied().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn buffer_add_manufactured(&mut self, manufactured: &Manufactured, amount: u64) {
+        if let Ok(ComponentGroup::Production { component }) =
+            self.get_component_mut(&ComponentName::ProductionComponent)
+        {
+            component
+                .manufactured_input
+                .entry(*manufactured)
+                .or_insert(0)
+                .add_assign(amount);
+        }
+    }
+
+    fn buffer_take_manufactured(&mut self, manufactured: &Manufactured) -> u64 {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component
+                .manufactured_input
+                .remove(manufactured)
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn cycle_inputs(&self) -> HashMap<Resource, u64> {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component.consumed.clone(),
+            _ => HashMap::new(),
+        }
+    }
+
+    fn cycle_manufactured(&self) -> HashMap<Manufactured, u64> {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component.manufactured_consumed.clone(),
+            _ => HashMap::new(),
+        }
+    }
+
+    // Consumes one cycle worth of inputs from the buffer, or returns what the buffer is short of.
+    fn start_cycle(
+        &mut self,
+        required: &HashMap<Resource, u64>,
+        required_manufactured: &HashMap<Manufactured, u64>,
+    ) -> Option<StructureStatus> {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                // no room for what the cycle would make.
+                if component.finished() >= OUTPUT_BUFFER_CAPACITY {
+                    return Some(StructureStatus::OutputFull);
+                }
+
+                if let Some(missing) = component.missing(required) {
+                    return Some(StructureStatus::MissingResource(missing));
+                }
+
+                if let Some(missing) = component.missing_manufactured(required_manufactured) {
+                    return Some(StructureStatus::MissingManufactured(missing));
+                }
+
+                for (resource, amount) in required {
+                    component
+                        .input
+                        .get_mut(resource)
+                        .unwrap()
+                        .sub_assign(*amount);
+                }
+
+                for (manufactured, amount) in required_manufactured {
+                    component
+                        .manufactured_input
+                        .get_mut(manufactured)
+                        .unwrap()
+                        .sub_assign(*amount);
+                }
+
+                component.consumed = required.clone();
+                component.manufactured_consumed = required_manufactured.clone();
+                component.in_cycle = true;
+                component.progress = 0;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    // Moves the running cycle one tick ahead, returns true when it finished.
+    fn advance(&mut self) -> bool {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.progress.add_assign(1);
+
+                if component.progress < component.cycle_ticks {
+                    return false;
+                }
+
+                component.progress = 0;
+                component.in_cycle = false;
+                component.consumed.clear();
+                component.manufactured_consumed.clear();
+                component.output.add_assign(1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Stops the running cycle, its inputs go back into the buffer.
+    fn reset_cycle(&mut self) {
+        if let Ok(ComponentGroup::Production { component }) =
+            self.get_component_mut(&ComponentName::ProductionComponent)
+        {
+            for (resource, amount) in component.consumed.drain() {
+                component
+                    .input
+                    .entry(resource)
+                    .or_insert(0)
+                    .add_assign(amount);
+            }
+
+            for (manufactured, amount) in component.manufactured_consumed.drain() {
+                component
+                    .manufactured_input
+                    .entry(manufactured)
+                    .or_insert(0)
+                    .add_assign(amount);
+            }
+
+            component.progress = 0;
+            component.in_cycle = false;
+        }
+    }
+
+    // Empties the output buffer, returns the number of finished cycles it held.
+    fn take_output(&mut self) -> u64 {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                let output = component.output;
+                component.output = 0;
+                output
+            }
+            _ => 0,
+        }
+    }
+
+    fn finished(&self) -> u64 {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component.finished(),
+            _ => 0,
+        }
+    }
+
+    fn finished_manufactured(&self) -> HashMap<Manufactured, u64> {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component.finished_manufactured.clone(),
+            _ => HashMap::new(),
+        }
+    }
+
+    fn finished_commodities(&self) -> HashMap<Commodity, u64> {
+        match self.get_component(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => component.finished_commodities.clone(),
+            _ => HashMap::new(),
+        }
+    }
+
+    fn store_manufactured(&mut self, manufactured: &Manufactured, amount: u64) {
+        if let Ok(ComponentGroup::Production { component }) =
+            self.get_component_mut(&ComponentName::ProductionComponent)
+        {
+            component
+                .finished_manufactured
+                .entry(*manufactured)
+                .or_insert(0)
+                .add_assign(amount);
+        }
+    }
+
+    fn store_commodity(&mut self, commodity: &Commodity, amount: u64) {
+        if let Ok(ComponentGroup::Production { component }) =
+            self.get_component_mut(&ComponentName::ProductionComponent)
+        {
+            component
+                .finished_commodities
+                .entry(*commodity)
+                .or_insert(0)
+                .add_assign(amount);
+        }
+    }
+
+    // Empties the output buffer of refined goods so they can be shipped.
+    fn take_finished_manufactured(&mut self) -> HashMap<Manufactured, u64> {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.finished_manufactured.drain().collect()
+            }
+            _ => HashMap::new(),
+        }
+    }
+
+    // Empties the output buffer of commodities so they can be shipped.
+    fn take_finished_commodities(&mut self) -> HashMap<Commodity, u64> {
+        match self.get_component_mut(&ComponentName::ProductionComponent) {
+            Ok(ComponentGroup::Production { component }) => {
+                component.finished_commodities.drain().collect()
+            }
+            _ => HashMap::new(),
+        }
+    }
+}
+
 impl MineOutputTrait for StructureBlueprint {
     fn resource_out(&self) -> u64 {
         match self.get_component(&ComponentName::MineOutputComponent) {
-            ComponentGroup::MineOutput {
+            Ok(ComponentGroup::MineOutput {
                 component: MineOutputComponent { resource_out, .. },
-            } => *resource_out,
+            }) => *resource_out,
             _ => 0,
         }
     }
 
     fn manufactured_out(&self) -> u64 {
         match self.get_component(&ComponentName::MineOutputComponent) {
-            ComponentGroup::MineOutput {
+            Ok(ComponentGroup::MineOutput {
                 component:
                     MineOutputComponent {
                         manufactured_out, ..
                     },
-            } => *manufactured_out,
+            }) => *manufactured_out,
             _ => 0,
         }
     }
@@ -254,18 +908,18 @@ impl MineOutputTrait for StructureBlueprint {
 impl ResourceStorageTrait for StructureBlueprint {
     fn capacity(&self, group: &Resource) -> u64 {
         match self.get_component(&ComponentName::ResourceStorageComponent) {
-            ComponentGroup::ResourceStorage {
+            Ok(ComponentGroup::ResourceStorage {
                 component: ResourceStorageComponent { capacity, .. },
-            } => capacity[&group],
+            }) => capacity[&group],
             _ => 0,
         }
     }
 
     fn resource(&self, group: &Resource) -> u64 {
         match self.get_component(&ComponentName::ResourceStorageComponent) {
-            ComponentGroup::ResourceStorage {
+            Ok(ComponentGroup::ResourceStorage {
                 component: ResourceStorageComponent { resources, .. },
-            } => resources[&group],
+            }) => resources[&group],
             _ => 0,
         }
     }
@@ -274,7 +928,7 @@ impl ResourceStorageTrait for StructureBlueprint {
         let component = self.get_component_mut(&ComponentName::ResourceStorageComponent);
 
         match component {
-            ComponentGroup::ResourceStorage { ref mut component } => {
+            Ok(ComponentGroup::ResourceStorage { ref mut component }) => {
                 let free_capacity = component.capacity_free(group);
 
                 if free_capacity <= 0 {
@@ -295,9 +949,18 @@ impl ResourceStorageTrait for StructureBlueprint {
         }
     }
 
+    fn resource_take(&mut self, group: &Resource, amount: u64) -> u64 {
+        match self.get_component_mut(&ComponentName::ResourceStorageComponent) {
+            Ok(ComponentGroup::ResourceStorage { component }) => {
+                component.resource_take(group, amount)
+            }
+            _ => 0,
+        }
+    }
+
     fn resources(&self) -> Vec<&Resource> {
         match self.get_component(&ComponentName::ResourceStorageComponent) {
-            ComponentGroup::ResourceStorage { component } => component.resources(),
+            Ok(ComponentGroup::ResourceStorage { component }) => component.resources(),
             _ => Vec::new(),
         }
     }
@@ -306,18 +969,18 @@ impl ResourceStorageTrait for StructureBlueprint {
 impl CommodityStorageTrait for StructureBlueprint {
     fn capacity(&self, group: &Commodity) -> u64 {
         match self.get_component(&ComponentName::CommodityStorageComponent) {
-            ComponentGroup::CommodityStorage {
+            Ok(ComponentGroup::CommodityStorage {
                 component: CommodityStorageComponent { capacity, .. },
-            } => capacity[&group],
+            }) => capacity[&group],
             _ => 0,
         }
     }
 
     fn commodity(&self, group: &Commodity) -> u64 {
         match self.get_component(&ComponentName::CommodityStorageComponent) {
-            ComponentGroup::CommodityStorage {
+            Ok(ComponentGroup::CommodityStorage {
                 component: CommodityStorageComponent { commodities, .. },
-            } => commodities[&group],
+            }) => commodities[&group],
             _ => 0,
         }
     }
@@ -326,7 +989,7 @@ impl CommodityStorageTrait for StructureBlueprint {
         let component = self.get_component_mut(&ComponentName::CommodityStorageComponent);
 
         match component {
-            ComponentGroup::CommodityStorage { ref mut component } => {
+            Ok(ComponentGroup::CommodityStorage { ref mut component }) => {
                 let free_capacity = component.capacity_free(group);
 
                 if free_capacity <= 0 {
@@ -349,7 +1012,7 @@ impl CommodityStorageTrait for StructureBlueprint {
 
     fn commodities(&self) -> Vec<&Commodity> {
         match self.get_component(&ComponentName::CommodityStorageComponent) {
-            ComponentGroup::CommodityStorage { component } => component.commodities(),
+            Ok(ComponentGroup::CommodityStorage { component }) => component.commodities(),
             _ => Vec::new(),
         }
     }
@@ -362,7 +1025,7 @@ pub struct Base {
 
 impl Debug for Base {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{:?}", self)
+        f.debug_struct("Base").finish_non_exhaustive()
     }
 }
 
@@ -395,13 +1058,23 @@ impl Base {
             component: ResourceStorageComponent::new(storage_resources),
         };
 
+        // The base always looks around itself, but cannot survey deposits.
+        let sight_component = ComponentGroup::Sight {
+            component: SightComponent {
+                radius: 6,
+                survey: false,
+                active: true,
+            },
+        };
+
         let mut components = HashMap::new();
 
         components.insert(ComponentName::EnergyComponent, energy_component);
         components.insert(ComponentName::BatteryComponent, battery_component);
         components.insert(ComponentName::ResourceStorageComponent, storage_component);
+        components.insert(ComponentName::SightComponent, sight_component);
 
-        let blueprint = StructureBlueprint { components };
+        let blueprint = StructureBlueprint::new(components);
 
         return Base { blueprint };
     }
@@ -422,15 +1095,20 @@ pub struct PowerPlant {
 
 impl Debug for PowerPlant {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{:?}", self)
+        f.debug_struct("PowerPlant").finish_non_exhaustive()
     }
 }
 
 impl PowerPlant {
-    pub fn new() -> PowerPlant {
+    // Low ground has denser air and cools the plant better, high ground does the opposite.
+    pub fn efficiency(height: f64) -> f64 {
+        return (1.0 - height * 0.25).clamp(0.75, 1.25);
+    }
+
+    pub fn new(height: f64) -> PowerPlant {
         let energy_component = ComponentGroup::Energy {
             component: EnergyComponent {
-                energy_out: 100,
+                energy_out: (100.0 * Self::efficiency(height)).round() as u64,
                 energy_in: 0,
             },
         };
@@ -438,7 +1116,7 @@ impl PowerPlant {
         let mut components = HashMap::new();
         components.insert(ComponentName::EnergyComponent, energy_component);
 
-        let blueprint = StructureBlueprint { components };
+        let blueprint = StructureBlueprint::new(components);
 
         return PowerPlant { blueprint };
     }
@@ -452,6 +1130,46 @@ impl PowerPlant {
     }
 }
 
+// SolarPanel
+pub struct SolarPanel {
+    blueprint: StructureBlueprint,
+}
+
+impl Debug for SolarPanel {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_struct("SolarPanel")
+            .field("status", &self.blueprint.status())
+            .finish()
+    }
+}
+
+impl SolarPanel {
+    // Output at noon, the energy manager scales it with the sunlight.
+    pub fn new() -> SolarPanel {
+        let energy_component = ComponentGroup::Energy {
+            component: EnergyComponent {
+                energy_out: 80,
+                energy_in: 0,
+            },
+        };
+
+        let mut components = HashMap::new();
+        components.insert(ComponentName::EnergyComponent, energy_component);
+
+        let blueprint = StructureBlueprint::new(components);
+
+        return SolarPanel { blueprint };
+    }
+
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        return &self.blueprint;
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        return &mut self.blueprint;
+    }
+}
+
 // Mine
 pub struct Mine {
     blueprint: StructureBlueprint,
@@ -461,7 +1179,9 @@ pub struct Mine {
 
 impl Debug for Mine {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{:?}", self)
+        f.debug_struct("Mine")
+            .field("resource", &self.resource)
+            .finish_non_exhaustive()
     }
 }
 
@@ -488,7 +1208,7 @@ impl Mine {
 
         let manufactured = Manufactured::Gravel;
 
-        let blueprint = StructureBlueprint { components };
+        let blueprint = StructureBlueprint::new(components);
 
         return Mine {
             blueprint,
@@ -521,7 +1241,7 @@ pub struct Storage {
 
 impl Debug for Storage {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{:?}", self)
+        f.debug_struct("Storage").finish_non_exhaustive()
     }
 }
 
@@ -547,7 +1267,7 @@ impl Storage {
             commodity_storage_component,
         );
 
-        let blueprint = StructureBlueprint { components };
+        let blueprint = StructureBlueprint::new(components);
 
         return Storage { blueprint };
     }
@@ -561,7 +1281,232 @@ impl Storage {
     }
 }
 
-pub struct ResourceRequireFactory {}
+// Radar
+pub struct Radar {
+    blueprint: StructureBlueprint,
+}
+
+impl Debug for Radar {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_struct("Radar").finish_non_exhaustive()
+    }
+}
+
+impl Radar {
+    pub fn new() -> Radar {
+        let energy_component = ComponentGroup::Energy {
+            component: EnergyComponent {
+                energy_out: 0,
+                energy_in: 15,
+            },
+        };
+
+        // Only sees while powered, the update loop switches it on and off.
+        let sight_component = ComponentGroup::Sight {
+            component: SightComponent {
+                radius: 12,
+                survey: true,
+                active: false,
+            },
+        };
+
+        let mut components = HashMap::new();
+        components.insert(ComponentName::EnergyComponent, energy_component);
+        components.insert(ComponentName::SightComponent, sight_component);
+
+        let blueprint = StructureBlueprint::new(components);
+
+        return Radar { blueprint };
+    }
+
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        return &self.blueprint;
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        return &mut self.blueprint;
+    }
+}
+
+// Shield
+pub struct Shield {
+    blueprint: StructureBlueprint,
+}
+
+impl Debug for Shield {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_struct("Shield")
+            .field("status", &self.blueprint.status())
+            .finish()
+    }
+}
+
+impl Shield {
+    pub fn new() -> Shield {
+        let energy_component = ComponentGroup::Energy {
+            component: EnergyComponent {
+                energy_out: 0,
+                energy_in: 25,
+            },
+        };
+
+        let mut components = HashMap::new();
+        components.insert(ComponentName::EnergyComponent, energy_component);
+
+        let blueprint = StructureBlueprint::new(components);
+
+        return Shield { blueprint };
+    }
+
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        return &self.blueprint;
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        return &mut self.blueprint;
+    }
+}
+
+// Terraformer
+pub struct Terraformer {
+    blueprint: StructureBlueprint,
+    project: TerraformProject,
+    // The tile the next finished cycle changes, None once nothing in reach is left.
+    target: Option<Position>,
+}
+
+impl Debug for Terraformer {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_struct("Terraformer")
+            .field("project", &self.project)
+            .field("target", &self.target)
+            .field("status", &self.blueprint.status())
+            .finish()
+    }
+}
+
+impl Terraformer {
+    pub fn new(project: TerraformProject) -> Terraformer {
+        let energy_component = ComponentGroup::Energy {
+            component: EnergyComponent {
+                energy_out: 0,
+                energy_in: 40,
+            },
+        };
+
+        let production_component = ComponentGroup::Production {
+            component: ProductionComponent::new(TERRAFORM_CYCLE_TICKS),
+        };
+
+        let mut components = HashMap::new();
+        components.insert(ComponentName::EnergyComponent, energy_component);
+        components.insert(ComponentName::ProductionComponent, production_component);
+
+        let blueprint = StructureBlueprint::new(components);
+
+        return Terraformer {
+            blueprint,
+            project,
+            target: None,
+        };
+    }
+
+    pub fn project(&self) -> TerraformProject {
+        return self.project;
+    }
+
+    // Switching projects throws away the cycle under way, like retooling a factory.
+    pub fn retool(&mut self, project: TerraformProject) {
+        self.project = project;
+        self.blueprint.reset_cycle();
+        self.blueprint.set_downtime(RETOOL_TICKS);
+    }
+
+    pub fn target(&self) -> Option<&Position> {
+        return self.target.as_ref();
+    }
+
+    pub fn set_target(&mut self, target: Option<Position>) {
+        self.target = target;
+    }
+
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        return &self.blueprint;
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        return &mut self.blueprint;
+    }
+}
+
+// Scrubber
+pub struct Scrubber {
+    blueprint: StructureBlueprint,
+}
+
+impl Debug for Scrubber {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_struct("Scrubber")
+            .field("status", &self.blueprint.status())
+            .finish()
+    }
+}
+
+impl Scrubber {
+    pub fn new() -> Scrubber {
+        let energy_component = ComponentGroup::Energy {
+            component: EnergyComponent {
+                energy_out: 0,
+                energy_in: 30,
+            },
+        };
+
+        let mut components = HashMap::new();
+        components.insert(ComponentName::EnergyComponent, energy_component);
+
+        let blueprint = StructureBlueprint::new(components);
+
+        return Scrubber { blueprint };
+    }
+
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        return &self.blueprint;
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        return &mut self.blueprint;
+    }
+}
+
+// Road
+pub struct Road {
+    blueprint: StructureBlueprint,
+}
+
+impl Debug for Road {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        f.debug_struct("Road")
+            .field("status", &self.blueprint.status())
+            .finish()
+    }
+}
+
+impl Road {
+    pub fn new() -> Road {
+        let blueprint = StructureBlueprint::new(HashMap::new());
+        return Road { blueprint };
+    }
+
+    pub fn blueprint(&self) -> &StructureBlueprint {
+        return &self.blueprint;
+    }
+
+    pub fn blueprint_mut(&mut self) -> &mut StructureBlueprint {
+        return &mut self.blueprint;
+    }
+}
+
+pub struct ResourceRequireFactory {}
 
 impl ResourceRequireFactory {
     fn energy_for_manufactured(resource: &Manufactured) -> u64 {
@@ -658,7 +1603,9 @@ pub struct Factory {
 
 impl Debug for Factory {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{:?}", self)
+        f.debug_struct("Factory")
+            .field("commodity", &self.commodity)
+            .finish_non_exhaustive()
     }
 }
 
@@ -671,22 +1618,18 @@ impl Factory {
             },
         };
 
-        let energy_required = CommodityRequireFactory::energy_for_commodity(&commodity);
-        let resource_required = CommodityRequireFactory::resources_for_commodity(&commodity);
+        let commodity_component = Self::output_component(&commodity);
 
-        let commodity_component = ComponentGroup::FactoryOutput {
-            component: FactoryOutputComponent {
-                commodity_out: 1,
-                energy_required,
-                resource_required,
-            },
+        let production_component = ComponentGroup::Production {
+            component: ProductionComponent::new(FACTORY_CYCLE_TICKS),
         };
 
         let mut components = HashMap::new();
         components.insert(ComponentName::EnergyComponent, energy_component);
         components.insert(ComponentName::FactoryOutputComponent, commodity_component);
+        components.insert(ComponentName::ProductionComponent, production_component);
 
-        let blueprint = StructureBlueprint { components };
+        let blueprint = StructureBlueprint::new(components);
 
         return Factory {
             blueprint,
@@ -694,6 +1637,30 @@ impl Factory {
         };
     }
 
+    fn output_component(commodity: &Commodity) -> ComponentGroup {
+        let energy_required = CommodityRequireFactory::energy_for_commodity(commodity);
+        let resource_required = CommodityRequireFactory::resources_for_commodity(commodity);
+
+        return ComponentGroup::FactoryOutput {
+            component: FactoryOutputComponent {
+                commodity_out: 1,
+                energy_required,
+                resource_required,
+            },
+        };
+    }
+
+    // Switches the factory to another commodity, it stands still while retooling.
+    pub fn retool(&mut self, commodity: Commodity) {
+        self.blueprint.add_component(
+            ComponentName::FactoryOutputComponent,
+            Self::output_component(&commodity),
+        );
+        self.blueprint.reset_cycle();
+        self.blueprint.set_downtime(RETOOL_TICKS);
+        self.commodity = commodity;
+    }
+
     pub fn blueprint(&self) -> &StructureBlueprint {
         return &self.blueprint;
     }
@@ -715,7 +1682,9 @@ pub struct Refinery {
 
 impl Debug for Refinery {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "{:?}", self)
+        f.debug_struct("Refinery")
+            .field("resources", &self.resources)
+            .finish_non_exhaustive()
     }
 }
 
@@ -728,6 +1697,26 @@ impl Refinery {
             },
         };
 
+        let refinery_component = Self::output_component(&resources);
+
+        let production_component = ComponentGroup::Production {
+            component: ProductionComponent::new(REFINERY_CYCLE_TICKS),
+        };
+
+        let mut components = HashMap::new();
+        components.insert(ComponentName::EnergyComponent, energy_component);
+        components.insert(ComponentName::RefineryOutputComponent, refinery_component);
+        components.insert(ComponentName::ProductionComponent, production_component);
+
+        let blueprint = StructureBlueprint::new(components);
+
+        return Refinery {
+            blueprint,
+            resources,
+        };
+    }
+
+    fn output_component(resources: &[Manufactured]) -> ComponentGroup {
         let mut manufactured_out = HashMap::new();
         let mut energy_required = HashMap::new();
         let mut resource_required = HashMap::new();
@@ -744,24 +1733,24 @@ impl Refinery {
             );
         }
 
-        let refinery_component = ComponentGroup::RefineryOutput {
+        return ComponentGroup::RefineryOutput {
             component: RefineryOutputComponent {
                 manufactured_out,
                 energy_required,
                 resource_required,
             },
         };
+    }
 
-        let mut components = HashMap::new();
-        components.insert(ComponentName::EnergyComponent, energy_component);
-        components.insert(ComponentName::RefineryOutputComponent, refinery_component);
-
-        let blueprint = StructureBlueprint { components };
-
-        return Refinery {
-            blueprint,
-            resources,
-        };
+    // Switches the refinery to other products, it stands still while retooling.
+    pub fn retool(&mut self, resources: Vec<Manufactured>) {
+        self.blueprint.add_component(
+            ComponentName::RefineryOutputComponent,
+            Self::output_component(&resources),
+        );
+        self.blueprint.reset_cycle();
+        self.blueprint.set_downtime(RETOOL_TICKS);
+        self.resources = resources;
     }
 
     pub fn blueprint(&self) -> &StructureBlueprint {
@@ -777,52 +1766,246 @@ impl Refinery {
     }
 }
 
+// Update ticks a factory or refinery stands still after switching recipes.
+pub const RETOOL_TICKS: u64 = 20;
+
+// Tiles around a running shield that hazards cannot reach.
+pub const SHIELD_RADIUS: f64 = 6.0;
+
+// Update ticks one production cycle of a refinery takes.
+pub const REFINERY_CYCLE_TICKS: u64 = 4;
+
+// Update ticks one production cycle of a factory takes.
+pub const FACTORY_CYCLE_TICKS: u64 = 6;
+
+// How many cycles worth of inputs a producer keeps in its buffer.
+pub const INPUT_BUFFER_CYCLES: u64 = 2;
+
+// Finished goods a producer holds before it stops starting new cycles.
+pub const OUTPUT_BUFFER_CAPACITY: u64 = 4;
+
+// Share of the construction cost returned when a structure is demolished.
+pub const SALVAGE_REFUND_PERCENT: u64 = 50;
+
+// Slopes above this need extra material for foundations.
+pub const GENTLE_SLOPE: f64 = 0.1;
+
+// Nothing can be built on slopes steeper than this.
+pub const MAX_BUILD_SLOPE: f64 = 0.2;
+
+pub struct StructureCostFactory {}
+
+impl StructureCostFactory {
+    pub fn cost_for_structure(group: &StructureGroup) -> HashMap<Resource, u64> {
+        let mut cost = HashMap::new();
+
+        match group {
+            // Base and mines are free, so a new colony can always bootstrap itself.
+            StructureGroup::Base => {}
+            StructureGroup::Mine => {}
+            StructureGroup::Power => {
+                cost.insert(Resource::Iron, 50);
+                cost.insert(Resource::Aluminum, 25);
+            }
+            StructureGroup::Solar => {
+                cost.insert(Resource::Aluminum, 20);
+                cost.insert(Resource::Silica, 30);
+            }
+            StructureGroup::Refinery => {
+                cost.insert(Resource::Iron, 40);
+                cost.insert(Resource::Silica, 20);
+            }
+            StructureGroup::Factory => {
+                cost.insert(Resource::Iron, 60);
+                cost.insert(Resource::Aluminum, 30);
+                cost.insert(Resource::Silica, 20);
+            }
+            StructureGroup::Storage => {
+                cost.insert(Resource::Iron, 30);
+                cost.insert(Resource::Aluminum, 10);
+            }
+            StructureGroup::Radar => {
+                cost.insert(Resource::Iron, 20);
+                cost.insert(Resource::Aluminum, 20);
+                cost.insert(Resource::Silica, 10);
+            }
+            StructureGroup::Road => {
+                cost.insert(Resource::Silica, 2);
+            }
+            StructureGroup::Shield => {
+                cost.insert(Resource::Iron, 40);
+                cost.insert(Resource::Aluminum, 30);
+                cost.insert(Resource::Silica, 20);
+            }
+            StructureGroup::Terraformer => {
+                cost.insert(Resource::Iron, 80);
+                cost.insert(Resource::Aluminum, 40);
+                cost.insert(Resource::Carbon, 30);
+            }
+            StructureGroup::Scrubber => {
+                cost.insert(Resource::Iron, 30);
+                cost.insert(Resource::Aluminum, 20);
+                cost.insert(Resource::Carbon, 20);
+            }
+        }
+
+        return cost;
+    }
+
+    // Every base after the landing one is an outpost, and outposts are not free.
+    pub fn cost_for_outpost() -> HashMap<Resource, u64> {
+        let mut cost = HashMap::new();
+
+        cost.insert(Resource::Iron, 150);
+        cost.insert(Resource::Aluminum, 100);
+        cost.insert(Resource::Silica, 50);
+
+        return cost;
+    }
+
+    // Construction cost raised by up to double on slopes between gentle and the maximum.
+    pub fn cost_for_placement(group: &StructureGroup, slope: f64) -> HashMap<Resource, u64> {
+        return Self::with_slope(Self::cost_for_structure(group), slope);
+    }
+
+    pub fn with_slope(mut cost: HashMap<Resource, u64>, slope: f64) -> HashMap<Resource, u64> {
+        let steepness = (slope - GENTLE_SLOPE) / (MAX_BUILD_SLOPE - GENTLE_SLOPE);
+        let surcharge = (steepness.clamp(0.0, 1.0) * 100.0).round() as u64;
+
+        for amount in cost.values_mut() {
+            *amount = *amount * (100 + surcharge) / 100;
+        }
+
+        return cost;
+    }
+
+    // Share of what was paid for a structure, outposts and slope surcharges included.
+    pub fn refund_for_cost(cost: &HashMap<Resource, u64>) -> HashMap<Resource, u64> {
+        let mut refund = cost.clone();
+
+        for amount in refund.values_mut() {
+            *amount = *amount * SALVAGE_REFUND_PERCENT / 100;
+        }
+
+        return refund;
+    }
+}
+
 pub struct StructureFactory {}
 
 impl StructureFactory {
+    // Switches a built factory or refinery to the recipe selected in the build menu.
+    // Returns the new recipe on success.
+    pub fn retool(
+        structure: &mut Structure,
+        refinery_select: &dyn MenuSelector<Vec<Manufactured>>,
+        factory_select: &dyn MenuSelector<Commodity>,
+        terraform_select: &dyn MenuSelector<TerraformProject>,
+    ) -> StdResult<String, RetoolError> {
+        let name = structure.to_string();
+
+        if structure.blueprint().downtime() > 0 {
+            return Err(RetoolError::Retooling { structure: name });
+        }
+
+        match structure {
+            Structure::Refinery { structure } => {
+                let resources = refinery_select.selected();
+                let recipe = resources.iter().join("/");
+
+                if structure.resources == resources {
+                    return Err(RetoolError::SameRecipe {
+                        structure: name,
+                        recipe,
+                    });
+                }
+
+                structure.retool(resources);
+                Ok(recipe)
+            }
+            Structure::Factory { structure } => {
+                let commodity = factory_select.selected();
+                let recipe = commodity.to_string();
+
+                if *structure.commodity() == commodity {
+                    return Err(RetoolError::SameRecipe {
+                        structure: name,
+                        recipe,
+                    });
+                }
+
+                structure.retool(commodity);
+                Ok(recipe)
+            }
+            Structure::Terraformer { structure } => {
+                let project = terraform_select.selected();
+                let recipe = project.to_string();
+
+                if structure.project() == project {
+                    return Err(RetoolError::SameRecipe {
+                        structure: name,
+                        recipe,
+                    });
+                }
+
+                structure.retool(project);
+                Ok(recipe)
+            }
+            _ => Err(RetoolError::NotRetoolable { structure: name }),
+        }
+    }
+
     pub fn new(
         group: &StructureGroup,
+        tile: &MapTile,
         object: Option<&MapObject>,
         resource_manager: &ResourceManager,
         refinery_select: &dyn MenuSelector<Vec<Manufactured>>,
         factory_select: &dyn MenuSelector<Commodity>,
-    ) -> Option<Structure> {
+        terraform_select: &dyn MenuSelector<TerraformProject>,
+    ) -> StdResult<Structure, PlacementError> {
         match group {
             StructureGroup::Base => {
                 let structure = Structure::Base {
                     structure: Base::new(),
                 };
-                Option::from(structure)
+                Ok(structure)
             }
             StructureGroup::Power => {
                 let structure = Structure::PowerPlant {
-                    structure: PowerPlant::new(),
+                    structure: PowerPlant::new(tile.height),
                 };
-                Option::from(structure)
+                Ok(structure)
+            }
+            StructureGroup::Solar => {
+                let structure = Structure::SolarPanel {
+                    structure: SolarPanel::new(),
+                };
+                Ok(structure)
             }
             StructureGroup::Mine => {
-                if object.is_none() {
-                    panic!("cannot build mine, map object missing!")
-                }
+                let deposit = object
+                    .and_then(|o| o.deposit.as_ref())
+                    .ok_or(PlacementError::MissingDeposit { group: *group })?;
 
-                let map_resource = object.unwrap().deposit.unwrap().resource.clone();
+                let map_resource = deposit.resource;
 
                 let structure = Structure::Mine {
                     structure: Mine::new(map_resource),
                 };
-                Option::from(structure)
+                Ok(structure)
             }
             StructureGroup::Refinery => {
                 let structure = Structure::Refinery {
                     structure: Refinery::new(refinery_select.selected()),
                 };
-                Option::from(structure)
+                Ok(structure)
             }
             StructureGroup::Factory => {
                 let structure = Structure::Factory {
                     structure: { Factory::new(factory_select.selected()) },
                 };
-                Option::from(structure)
+                Ok(structure)
             }
             StructureGroup::Storage => {
                 let structure = Structure::Storage {
@@ -831,32 +2014,230 @@ impl StructureFactory {
                         resource_manager.commodity_types(),
                     ),
                 };
-                Option::from(structure)
+                Ok(structure)
+            }
+            StructureGroup::Radar => {
+                let structure = Structure::Radar {
+                    structure: Radar::new(),
+                };
+                Ok(structure)
+            }
+            StructureGroup::Road => {
+                let structure = Structure::Road {
+                    structure: Road::new(),
+                };
+                Ok(structure)
+            }
+            StructureGroup::Shield => {
+                let structure = Structure::Shield {
+                    structure: Shield::new(),
+                };
+                Ok(structure)
+            }
+            StructureGroup::Terraformer => {
+                let structure = Structure::Terraformer {
+                    structure: Terraformer::new(terraform_select.selected()),
+                };
+                Ok(structure)
+            }
+            StructureGroup::Scrubber => {
+                let structure = Structure::Scrubber {
+                    structure: Scrubber::new(),
+                };
+                Ok(structure)
             }
         }
     }
 
-    pub fn allowed(group: &StructureGroup, tile: &MapTile) -> bool {
-        match group {
-            StructureGroup::Base => {
-                !tile.is_resource && (tile.flora == Flora::Sand || tile.flora == Flora::Grass)
+    // What it costs to build a structure at a position, including slope surcharge.
+    pub fn cost(
+        group: &StructureGroup,
+        position: &Position,
+        controller: &MapController,
+    ) -> HashMap<Resource, u64> {
+        let slope = controller.footprint_slope(position, &group.footprint());
+
+        if group == &StructureGroup::Base && !controller.base_cells().is_empty() {
+            return StructureCostFactory::with_slope(
+                StructureCostFactory::cost_for_outpost(),
+                slope,
+            );
+        }
+
+        return StructureCostFactory::cost_for_placement(group, slope);
+    }
+
+    // Checks everything that can stop a structure from being placed at a position,
+    // every tile under its footprint, what already stands there, the slope and the cost.
+    pub fn validate(
+        group: &StructureGroup,
+        position: &Position,
+        controller: &MapController,
+        resource_manager: &ResourceManager,
+        rover_manager: &RoverManager,
+        logistics: &LogisticsManager,
+    ) -> StdResult<(), PlacementError> {
+        let footprint = group.footprint();
+
+        for cell in footprint.cells(position) {
+            if !controller.map().contains(&cell) {
+                return Err(PlacementError::OutOfBounds {
+                    group: *group,
+                    footprint,
+                });
             }
-            StructureGroup::Power => {
-                !tile.is_resource && (tile.flora == Flora::Sand || tile.flora == Flora::Water)
+
+            if !controller.tile_at(&cell).explored {
+                return Err(PlacementError::Unexplored { position: cell });
             }
-            StructureGroup::Mine => tile.is_resource,
-            StructureGroup::Storage => {
-                !tile.is_resource && (tile.flora == Flora::Sand || tile.flora == Flora::Grass)
+
+            if let Some(structure) = controller
+                .object_at(&cell)
+                .and_then(|o| o.structure.as_ref())
+            {
+                return Err(PlacementError::Occupied {
+                    position: cell,
+                    structure: structure.to_string(),
+                });
+            }
+
+            if let Some(rover) = rover_manager.at(&cell) {
+                return Err(PlacementError::RoverInTheWay {
+                    position: cell,
+                    kind: rover.kind(),
+                });
+            }
+
+            let tile = controller.tile_at(&cell);
+            if !Self::allowed(group, tile, &controller.map().planet()) {
+                return Err(PlacementError::NotAllowed {
+                    group: *group,
+                    flora: tile.flora,
+                    is_resource: tile.is_resource,
+                });
             }
+        }
+
+        // Once a base stands, everything else has to be reachable from one.
+        let cells = footprint.cells(position);
+        let reachable = logistics.reachable();
+        if group != &StructureGroup::Base
+            && !reachable.is_empty()
+            && Pathfinder::closest(reachable, &cells).is_none()
+        {
+            return Err(PlacementError::Unreachable {
+                group: *group,
+                position: position.clone(),
+            });
+        }
+
+        let slope = controller.footprint_slope(position, &footprint);
+        if slope > MAX_BUILD_SLOPE {
+            return Err(PlacementError::TooSteep {
+                group: *group,
+                slope,
+            });
+        }
+
+        let cost = Self::cost(group, position, controller);
+        if !resource_manager.has_resources(&cost, controller.objects(), logistics) {
+            return Err(PlacementError::InsufficientResources {
+                group: *group,
+                cost,
+            });
+        }
+
+        return Ok(());
+    }
+
+    pub fn allowed(group: &StructureGroup, tile: &MapTile, planet: &PlanetType) -> bool {
+        if group == &StructureGroup::Mine {
+            return tile.is_resource;
+        }
+
+        if tile.is_resource {
+            return false;
+        }
+
+        let floras = match group {
+            StructureGroup::Base => vec![Flora::Sand, Flora::Grass, Flora::SaltFlat, Flora::Crater],
+            StructureGroup::Power => vec![Flora::Sand, Flora::Water, Flora::Lava],
+            StructureGroup::Solar => vec![
+                Flora::Sand,
+                Flora::Dirt,
+                Flora::Rock,
+                Flora::Crater,
+                Flora::SaltFlat,
+                Flora::Ice,
+            ],
+            StructureGroup::Mine => vec![],
+            StructureGroup::Storage => vec![
+                Flora::Sand,
+                Flora::Grass,
+                Flora::SaltFlat,
+                Flora::Crater,
+                Flora::Ice,
+            ],
             StructureGroup::Factory => {
-                !tile.is_resource && (tile.flora == Flora::Grass || tile.flora == Flora::Dirt)
+                vec![Flora::Grass, Flora::Dirt, Flora::SaltFlat, Flora::Crater]
             }
             StructureGroup::Refinery => {
-                !tile.is_resource
-                    && (tile.flora == Flora::Sand
-                        || tile.flora == Flora::Dirt
-                        || tile.flora == Flora::Grass)
+                vec![Flora::Sand, Flora::Dirt, Flora::Grass, Flora::SaltFlat]
             }
-        }
+            StructureGroup::Radar => vec![
+                Flora::Sand,
+                Flora::Dirt,
+                Flora::Grass,
+                Flora::Rock,
+                Flora::Crater,
+                Flora::SaltFlat,
+                Flora::Ice,
+            ],
+            StructureGroup::Shield | StructureGroup::Scrubber => vec![
+                Flora::Sand,
+                Flora::Dirt,
+                Flora::Grass,
+                Flora::Rock,
+                Flora::Crater,
+                Flora::SaltFlat,
+                Flora::Ice,
+            ],
+            StructureGroup::Terraformer => vec![
+                Flora::Sand,
+                Flora::Dirt,
+                Flora::Grass,
+                Flora::Rock,
+                Flora::Crater,
+                Flora::SaltFlat,
+            ],
+            StructureGroup::Road => vec![
+                Flora::Sand,
+                Flora::Dirt,
+                Flora::Grass,
+                Flora::Rock,
+                Flora::Crater,
+                Flora::SaltFlat,
+                Flora::Ice,
+                Flora::ToxicSwamp,
+                // A road over water is a bridge.
+                Flora::Water,
+            ],
+        };
+
+        // Some planets leave little usable ground, so their presets open up more of it.
+        let planet_floras = match (planet, group) {
+            (PlanetType::Frozen, StructureGroup::Base) => vec![Flora::Ice],
+            (PlanetType::Frozen, StructureGroup::Factory) => vec![Flora::Ice],
+            (PlanetType::Frozen, StructureGroup::Power) => vec![Flora::Ice, Flora::Dirt],
+            (PlanetType::Desert, StructureGroup::Power) => vec![Flora::SaltFlat],
+            (PlanetType::Volcanic, StructureGroup::Base) => vec![Flora::Rock, Flora::Dirt],
+            (PlanetType::Volcanic, StructureGroup::Storage) => vec![Flora::Rock],
+            (PlanetType::Volcanic, StructureGroup::Refinery) => vec![Flora::Crater, Flora::Rock],
+            (PlanetType::Ocean, StructureGroup::Factory) => vec![Flora::Sand],
+            (PlanetType::Ocean, StructureGroup::Storage) => vec![Flora::Water],
+            _ => vec![],
+        };
+
+        return floras.contains(&tile.flora) || planet_floras.contains(&tile.flora);
     }
 }
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+
+use crate::game::{Flora, Manufactured, Resource};
+
+// Tiles around a terraformer it can reach.
+pub const TERRAFORM_RADIUS: f64 = 5.0;
+
+// Update ticks a terraformer works on a single tile.
+pub const TERRAFORM_CYCLE_TICKS: u64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TerraformProject {
+    // Breaks rock down into dirt, then grows grass on it.
+    Greening,
+    // Floods sand into shallow water.
+    Flooding,
+}
+
+impl TerraformProject {
+    pub fn all() -> Vec<TerraformProject> {
+        return vec![TerraformProject::Greening, TerraformProject::Flooding];
+    }
+
+    // What a tile turns into after one cycle, None when the project leaves it alone.
+    pub fn convert(&self, flora: &Flora) -> Option<Flora> {
+        return match (self, flora) {
+            (TerraformProject::Greening, Flora::Rock) => Some(Flora::Dirt),
+            (TerraformProject::Greening, Flora::Dirt) => Some(Flora::Grass),
+            (TerraformProject::Flooding, Flora::Sand) => Some(Flora::Water),
+            _ => None,
+        };
+    }
+
+    // How far every finished cycle raises the oxygen share of the air in reach.
+    pub fn oxygen_step(&self) -> f64 {
+        return match self {
+            TerraformProject::Greening => 0.5,
+            TerraformProject::Flooding => 0.1,
+        };
+    }
+
+    // How far every finished cycle moves the air pressure in reach towards breathable, in kPa.
+    pub fn pressure_step(&self) -> f64 {
+        return match self {
+            TerraformProject::Greening => 0.5,
+            TerraformProject::Flooding => 2.0,
+        };
+    }
+
+    // How far every finished cycle moves the temperature in reach towards comfortable.
+    pub fn temperature_step(&self) -> f64 {
+        return match self {
+            TerraformProject::Greening => 0.25,
+            TerraformProject::Flooding => 1.0,
+        };
+    }
+
+    // Water shipped in by road and used up by every cycle.
+    pub fn resources(&self) -> HashMap<Resource, u64> {
+        let mut resources = HashMap::new();
+
+        match self {
+            TerraformProject::Greening => {
+                resources.insert(Resource::Water, 10);
+            }
+            TerraformProject::Flooding => {
+                resources.insert(Resource::Water, 40);
+            }
+        }
+
+        return resources;
+    }
+
+    // Oxygen shipped in by road and used up by every cycle.
+    pub fn manufactured(&self) -> HashMap<Manufactured, u64> {
+        let mut manufactured = HashMap::new();
+
+        match self {
+            TerraformProject::Greening => {
+                manufactured.insert(Manufactured::Oxygen, 8);
+            }
+            TerraformProject::Flooding => {
+                manufactured.insert(Manufactured::Oxygen, 2);
+            }
+        }
+
+        return manufactured;
+    }
+}
+
+impl Display for TerraformProject {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            TerraformProject::Greening => write!(f, "Greening"),
+            TerraformProject::Flooding => write!(f, "Flooding"),
+        }
+    }
+}
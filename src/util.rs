@@ -1,8 +1,14 @@
+use crate::game::Resource;
 use chrono::Local;
+use itertools::Itertools;
+use std::collections::HashMap;
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
+// Time between two update ticks.
+pub const UPDATE_RATE_MS: u64 = 240;
+
 pub enum GameEvent {
     Update,
     Draw,
@@ -26,7 +32,7 @@ pub struct Config {
 impl Default for Config {
     fn default() -> Config {
         Config {
-            update_rate: Duration::from_millis(240),
+            update_rate: Duration::from_millis(UPDATE_RATE_MS),
             draw_rate: Duration::from_millis(30),
             input_rate: Duration::from_millis(10),
         }
@@ -104,11 +110,38 @@ pub fn format_welcome_message() -> String {
     let mut message = String::from("Welcome!");
     message.push_str(" Use the arrow (or AWSD) keys to move on map.");
     message.push_str(" Use the ENTER to place structure or action.");
+    message.push_str(" Use DELETE to demolish a structure and salvage it.");
     message.push_str(" Use PageUp/PageDown and Home/End to navigate menus.");
+    message.push_str(" Use M to switch the map mode, O for the overview.");
+    message.push_str(" Use V to survey the deposit under the cursor.");
+    message.push_str(" Use R to retool a factory or refinery to the selected recipe.");
+    message.push_str(" Use TAB to switch to the statistics, bottleneck and structure screens.");
+    message.push_str(" Use B to print the top bottlenecks here.");
+    message.push_str(" Connect mines, refineries, factories and storage to a base with roads.");
+    message.push_str(" Use U on a base to build a rover, K to pick its kind, E to select one and G to send it to the cursor.");
+    message.push_str(" Solar Panels only work by day, keep the batteries charged for the night.");
+    message.push_str(
+        " Structures in very hot or cold places need extra energy, check the climate map modes.",
+    );
+    message.push_str(" Terraformers use Water and Oxygen to turn rock into grass or flood sand and make the air around them breathable, pick the project with Home/End.");
+    message.push_str(" Mines, refineries and factories pollute, Scrubbers clean the air and keep colonists healthy.");
+    message.push_str(" Watch for hazard warnings here, Shields and Radars help against them.");
     message.push_str(" Use ESC to exit the game.");
     return get_log(message);
 }
 
+pub fn format_resources(resources: &HashMap<Resource, u64>) -> String {
+    if resources.is_empty() {
+        return String::from("nothing");
+    }
+
+    return resources
+        .iter()
+        .sorted_by_key(|(resource, _)| resource.to_string())
+        .map(|(resource, amount)| format!("{} {}", resource, amount))
+        .join(", ");
+}
+
 pub struct Tick {
     delta: u128,
     duration: Duration,